@@ -576,10 +576,13 @@ impl CliFactory {
       .node_resolver
       .get_or_try_init_async(
         async {
-          Ok(Arc::new(NodeResolver::new(
-            self.fs().clone(),
-            self.npm_resolver().await?.clone().into_npm_resolver(),
-          )))
+          Ok(Arc::new(
+            NodeResolver::new(
+              self.fs().clone(),
+              self.npm_resolver().await?.clone().into_npm_resolver(),
+            )
+            .with_node_env(std::env::var("NODE_ENV").ok().as_deref()),
+          ))
         }
         .boxed_local(),
       )
@@ -638,6 +641,7 @@ impl CliFactory {
           self.fs().clone(),
           self.resolver().await?.clone(),
           self.npm_resolver().await?.clone(),
+          self.node_resolver().await?.clone(),
           self.module_info_cache()?.clone(),
           self.parsed_source_cache().clone(),
           self.maybe_lockfile().clone(),
@@ -783,6 +787,7 @@ impl CliFactory {
       self.options.sub_command().clone(),
       npm_resolver.clone(),
       node_resolver.clone(),
+      NodeAnalysisCache::new(self.caches()?.node_analysis_db()),
       self.blob_store().clone(),
       Box::new(CliModuleLoaderFactory::new(
         &self.options,
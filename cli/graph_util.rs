@@ -69,6 +69,7 @@ pub struct GraphValidOptions {
 pub fn graph_valid(
   graph: &ModuleGraph,
   fs: &Arc<dyn FileSystem>,
+  builtin_node_modules: &deno_node::BuiltinNodeModules,
   roots: &[ModuleSpecifier],
   options: GraphValidOptions,
 ) -> Result<(), AnyError> {
@@ -97,12 +98,18 @@ pub fn graph_valid(
       };
       let mut message = match &error {
         ModuleGraphError::ResolutionError(resolution_error) => {
-          enhanced_resolution_error_message(resolution_error)
+          enhanced_resolution_error_message(
+            resolution_error,
+            builtin_node_modules,
+          )
         }
         ModuleGraphError::TypesResolutionError(resolution_error) => {
           format!(
             "Failed resolving types. {}",
-            enhanced_resolution_error_message(resolution_error)
+            enhanced_resolution_error_message(
+              resolution_error,
+              builtin_node_modules
+            )
           )
         }
         ModuleGraphError::ModuleError(error) => {
@@ -352,6 +359,7 @@ pub struct ModuleGraphBuilder {
   fs: Arc<dyn FileSystem>,
   resolver: Arc<CliGraphResolver>,
   npm_resolver: Arc<dyn CliNpmResolver>,
+  node_resolver: Arc<deno_node::NodeResolver>,
   module_info_cache: Arc<ModuleInfoCache>,
   parsed_source_cache: Arc<ParsedSourceCache>,
   lockfile: Option<Arc<Mutex<Lockfile>>>,
@@ -369,6 +377,7 @@ impl ModuleGraphBuilder {
     fs: Arc<dyn FileSystem>,
     resolver: Arc<CliGraphResolver>,
     npm_resolver: Arc<dyn CliNpmResolver>,
+    node_resolver: Arc<deno_node::NodeResolver>,
     module_info_cache: Arc<ModuleInfoCache>,
     parsed_source_cache: Arc<ParsedSourceCache>,
     lockfile: Option<Arc<Mutex<Lockfile>>>,
@@ -383,6 +392,7 @@ impl ModuleGraphBuilder {
       fs,
       resolver,
       npm_resolver,
+      node_resolver,
       module_info_cache,
       parsed_source_cache,
       lockfile,
@@ -693,6 +703,7 @@ impl ModuleGraphBuilder {
     graph_valid(
       graph,
       &self.fs,
+      self.node_resolver.builtin_node_modules(),
       roots,
       GraphValidOptions {
         is_vendoring: false,
@@ -722,10 +733,15 @@ pub fn error_for_any_npm_specifier(
 }
 
 /// Adds more explanatory information to a resolution error.
-pub fn enhanced_resolution_error_message(error: &ResolutionError) -> String {
+pub fn enhanced_resolution_error_message(
+  error: &ResolutionError,
+  builtin_node_modules: &deno_node::BuiltinNodeModules,
+) -> String {
   let mut message = format!("{error}");
 
-  if let Some(specifier) = get_resolution_error_bare_node_specifier(error) {
+  if let Some(specifier) =
+    get_resolution_error_bare_node_specifier(error, builtin_node_modules)
+  {
     if !*DENO_DISABLE_PEDANTIC_NODE_WARNINGS {
       message.push_str(&format!(
         "\nIf you want to use a built-in Node module, add a \"node:\" prefix (ex. \"node:{specifier}\")."
@@ -832,11 +848,12 @@ fn enhanced_lockfile_error_message(err: &ModuleError) -> Option<String> {
   }
 }
 
-pub fn get_resolution_error_bare_node_specifier(
-  error: &ResolutionError,
-) -> Option<&str> {
+pub fn get_resolution_error_bare_node_specifier<'a>(
+  error: &'a ResolutionError,
+  builtin_node_modules: &deno_node::BuiltinNodeModules,
+) -> Option<&'a str> {
   get_resolution_error_bare_specifier(error)
-    .filter(|specifier| deno_node::is_builtin_node_module(specifier))
+    .filter(|specifier| builtin_node_modules.is_builtin(specifier))
 }
 
 fn get_resolution_error_bare_specifier(
@@ -1050,13 +1067,28 @@ mod test {
           end: Position::zeroed(),
         },
       };
-      assert_eq!(get_resolution_error_bare_node_specifier(&err), output);
+      assert_eq!(
+        get_resolution_error_bare_node_specifier(
+          &err,
+          &deno_node::BuiltinNodeModules::default()
+        ),
+        output
+      );
     }
   }
 
   #[test]
   fn bare_specifier_node_resolution_error() {
-    let cases = vec![("process", Some("process")), ("other", None)];
+    let builtin_node_modules =
+      deno_node::BuiltinNodeModules::new(vec![deno_node::NodeModulePolyfill {
+        name: "sqlite".to_string(),
+        specifier: "ext:embedder/sqlite.js".to_string(),
+      }]);
+    let cases = vec![
+      ("process", Some("process")),
+      ("sqlite", Some("sqlite")),
+      ("other", None),
+    ];
     for (input, output) in cases {
       let specifier = ModuleSpecifier::parse("file:///file.ts").unwrap();
       let err = ResolutionError::InvalidSpecifier {
@@ -1070,7 +1102,10 @@ mod test {
           referrer: None,
         },
       };
-      assert_eq!(get_resolution_error_bare_node_specifier(&err), output,);
+      assert_eq!(
+        get_resolution_error_bare_node_specifier(&err, &builtin_node_modules),
+        output,
+      );
     }
   }
 }
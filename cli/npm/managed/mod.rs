@@ -520,6 +520,19 @@ impl NpmResolver for ManagedCliNpmResolver {
     Ok(path)
   }
 
+  fn resolve_package_folder_from_path(
+    &self,
+    path: &Path,
+  ) -> Result<Option<PathBuf>, AnyError> {
+    let Ok(specifier) = ModuleSpecifier::from_file_path(path) else {
+      return Ok(None);
+    };
+    match self.resolve_pkg_id_from_specifier(&specifier)? {
+      Some(pkg_id) => self.resolve_pkg_folder_from_pkg_id(&pkg_id).map(Some),
+      None => Ok(None),
+    }
+  }
+
   fn in_npm_package(&self, specifier: &ModuleSpecifier) -> bool {
     let root_dir_url = self.fs_resolver.root_dir_url();
     debug_assert!(root_dir_url.as_str().ends_with('/'));
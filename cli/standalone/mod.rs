@@ -456,15 +456,15 @@ pub async fn run(
     };
 
   let has_node_modules_dir = npm_resolver.root_node_modules_path().is_some();
-  let node_resolver = Arc::new(NodeResolver::new(
-    fs.clone(),
-    npm_resolver.clone().into_npm_resolver(),
-  ));
+  let node_resolver = Arc::new(
+    NodeResolver::new(fs.clone(), npm_resolver.clone().into_npm_resolver())
+      .with_node_env(std::env::var("NODE_ENV").ok().as_deref()),
+  );
   let cjs_resolutions = Arc::new(CjsResolutionStore::default());
   let cache_db = Caches::new(deno_dir_provider.clone());
   let node_analysis_cache = NodeAnalysisCache::new(cache_db.node_analysis_db());
   let cjs_esm_code_analyzer =
-    CliCjsCodeAnalyzer::new(node_analysis_cache, fs.clone());
+    CliCjsCodeAnalyzer::new(node_analysis_cache.clone(), fs.clone());
   let node_code_translator = Arc::new(NodeCodeTranslator::new(
     cjs_esm_code_analyzer,
     fs.clone(),
@@ -539,6 +539,7 @@ pub async fn run(
     crate::args::DenoSubcommand::Run(Default::default()),
     npm_resolver,
     node_resolver,
+    node_analysis_cache,
     Default::default(),
     Box::new(module_loader_factory),
     root_cert_store_provider,
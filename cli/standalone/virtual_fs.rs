@@ -279,6 +279,7 @@ impl<'a> VfsEntryRef<'a> {
         is_symlink: false,
         atime: None,
         birthtime: None,
+        ctime: None,
         mtime: None,
         blksize: 0,
         size: 0,
@@ -301,6 +302,7 @@ impl<'a> VfsEntryRef<'a> {
         is_symlink: false,
         atime: None,
         birthtime: None,
+        ctime: None,
         mtime: None,
         blksize: 0,
         size: file.len,
@@ -323,6 +325,7 @@ impl<'a> VfsEntryRef<'a> {
         is_symlink: true,
         atime: None,
         birthtime: None,
+        ctime: None,
         mtime: None,
         blksize: 0,
         size: 0,
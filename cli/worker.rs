@@ -50,7 +50,9 @@ use tokio::select;
 use crate::args::write_lockfile_if_has_changes;
 use crate::args::DenoSubcommand;
 use crate::args::StorageKeyResolver;
+use crate::cache::NodeAnalysisCache;
 use crate::errors;
+use crate::node::CliCjsCodeAnalyzer;
 use crate::npm::CliNpmResolver;
 use crate::util::checksum;
 use crate::util::file_watcher::WatcherCommunicator;
@@ -130,6 +132,7 @@ struct SharedWorkerState {
   storage_key_resolver: StorageKeyResolver,
   npm_resolver: Arc<dyn CliNpmResolver>,
   node_resolver: Arc<NodeResolver>,
+  node_analysis_cache: NodeAnalysisCache,
   blob_store: Arc<BlobStore>,
   broadcast_channel: InMemoryBroadcastChannel,
   shared_array_buffer_store: SharedArrayBufferStore,
@@ -151,7 +154,16 @@ struct SharedWorkerState {
 }
 
 impl SharedWorkerState {
-  // Currently empty
+  /// Puts the analyzer used by `op_require_cjs_named_exports` in the op
+  /// state of a new worker.
+  fn put_cjs_code_analyzer(&self, js_runtime: &mut deno_core::JsRuntime) {
+    let analyzer: deno_node::analyze::CjsCodeAnalyzerRc =
+      Rc::new(CliCjsCodeAnalyzer::new(
+        self.node_analysis_cache.clone(),
+        self.fs.clone(),
+      ));
+    js_runtime.op_state().borrow_mut().put(analyzer);
+  }
 }
 
 pub struct CliMainWorker {
@@ -406,6 +418,7 @@ impl CliMainWorkerFactory {
     subcommand: DenoSubcommand,
     npm_resolver: Arc<dyn CliNpmResolver>,
     node_resolver: Arc<NodeResolver>,
+    node_analysis_cache: NodeAnalysisCache,
     blob_store: Arc<BlobStore>,
     module_loader_factory: Box<dyn ModuleLoaderFactory>,
     root_cert_store_provider: Arc<dyn RootCertStoreProvider>,
@@ -430,6 +443,7 @@ impl CliMainWorkerFactory {
         storage_key_resolver,
         npm_resolver,
         node_resolver,
+        node_analysis_cache,
         blob_store,
         broadcast_channel: Default::default(),
         shared_array_buffer_store: Default::default(),
@@ -654,6 +668,7 @@ impl CliMainWorkerFactory {
       permissions,
       options,
     );
+    shared.put_cjs_code_analyzer(&mut worker.js_runtime);
 
     if self.shared.subcommand.needs_test() {
       macro_rules! test_file {
@@ -853,13 +868,15 @@ fn create_web_worker_callback(
       maybe_worker_metadata: args.maybe_worker_metadata,
     };
 
-    WebWorker::bootstrap_from_options(
+    let (mut worker, handle) = WebWorker::bootstrap_from_options(
       args.name,
       args.permissions,
       args.main_module,
       args.worker_id,
       options,
-    )
+    );
+    shared.put_cjs_code_analyzer(&mut worker.js_runtime);
+    (worker, handle)
   })
 }
 
@@ -211,6 +211,7 @@ impl FileSystem for InMemoryFs {
           mtime: None,
           atime: None,
           birthtime: None,
+          ctime: None,
           dev: 0,
           ino: 0,
           mode: 0,
@@ -233,6 +234,7 @@ impl FileSystem for InMemoryFs {
           mtime: None,
           atime: None,
           birthtime: None,
+          ctime: None,
           dev: 0,
           ino: 0,
           mode: 0,
@@ -262,8 +264,14 @@ impl FileSystem for InMemoryFs {
     self.lstat_sync(&path)
   }
 
-  fn realpath_sync(&self, _path: &Path) -> FsResult<PathBuf> {
-    Err(FsError::NotSupported)
+  fn realpath_sync(&self, path: &Path) -> FsResult<PathBuf> {
+    // there are no symlinks, so an existing path is already canonical
+    let path = normalize_path(path);
+    if self.entries.lock().contains_key(&path) {
+      Ok(path)
+    } else {
+      Err(FsError::Io(Error::new(ErrorKind::NotFound, "Not found")))
+    }
   }
   async fn realpath_async(&self, path: PathBuf) -> FsResult<PathBuf> {
     self.realpath_sync(&path)
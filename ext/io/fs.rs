@@ -95,6 +95,8 @@ pub struct FsStat {
   pub mtime: Option<u64>,
   pub atime: Option<u64>,
   pub birthtime: Option<u64>,
+  /// The last time the file's status changed. Only available on unix.
+  pub ctime: Option<u64>,
 
   pub dev: u64,
   pub ino: u64,
@@ -163,6 +165,20 @@ impl FsStat {
       mtime: to_msec(metadata.modified()),
       atime: to_msec(metadata.accessed()),
       birthtime: to_msec(metadata.created()),
+      ctime: {
+        #[cfg(unix)]
+        {
+          use std::os::unix::fs::MetadataExt;
+          Some(
+            (metadata.ctime() * 1000 + metadata.ctime_nsec() / 1_000_000)
+              as u64,
+          )
+        }
+        #[cfg(not(unix))]
+        {
+          None
+        }
+      },
 
       dev: unix_or_zero!(dev),
       ino: unix_or_zero!(ino),
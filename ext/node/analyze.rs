@@ -4,6 +4,7 @@ use std::collections::BTreeSet;
 use std::collections::HashSet;
 use std::path::Path;
 use std::path::PathBuf;
+use std::rc::Rc;
 
 use deno_core::anyhow;
 use deno_core::anyhow::Context;
@@ -13,10 +14,10 @@ use deno_core::futures::FutureExt;
 use deno_core::futures::StreamExt;
 use deno_core::ModuleSpecifier;
 use once_cell::sync::Lazy;
+use serde::Serialize;
 
 use deno_core::error::AnyError;
 
-use crate::package_json::load_pkg_json;
 use crate::path::to_file_specifier;
 use crate::resolution::NodeResolverRc;
 use crate::NodeModuleKind;
@@ -32,7 +33,7 @@ pub enum CjsAnalysis {
   Cjs(CjsAnalysisExports),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct CjsAnalysisExports {
   pub exports: Vec<String>,
   pub reexports: Vec<String>,
@@ -55,6 +56,10 @@ pub trait CjsCodeAnalyzer {
   ) -> Result<CjsAnalysis, AnyError>;
 }
 
+/// The analyzer used by `op_require_cjs_named_exports`. Embedders that
+/// want the op to work put one in the op state.
+pub type CjsCodeAnalyzerRc = Rc<dyn CjsCodeAnalyzer>;
+
 pub struct NodeCodeTranslator<TCjsCodeAnalyzer: CjsCodeAnalyzer> {
   cjs_code_analyzer: TCjsCodeAnalyzer,
   fs: deno_fs::FileSystemRc,
@@ -311,7 +316,8 @@ impl<TCjsCodeAnalyzer: CjsCodeAnalyzer> NodeCodeTranslator<TCjsCodeAnalyzer> {
     )?;
 
     let package_json_path = module_dir.join("package.json");
-    let maybe_package_json = load_pkg_json(&*self.fs, &package_json_path)?;
+    let maybe_package_json =
+      self.node_resolver.load_package_json(&package_json_path)?;
     if let Some(package_json) = maybe_package_json {
       if let Some(exports) = &package_json.exports {
         return self.node_resolver.package_exports_resolve(
@@ -332,7 +338,7 @@ impl<TCjsCodeAnalyzer: CjsCodeAnalyzer> NodeCodeTranslator<TCjsCodeAnalyzer> {
           // subdir might have a package.json that specifies the entrypoint
           let package_json_path = d.join("package.json");
           let maybe_package_json =
-            load_pkg_json(&*self.fs, &package_json_path)?;
+            self.node_resolver.load_package_json(&package_json_path)?;
           if let Some(package_json) = maybe_package_json {
             if let Some(main) = package_json.main(NodeModuleKind::Cjs) {
               return Ok(to_file_specifier(&d.join(main).clean()));
@@ -2,6 +2,7 @@
 
 use std::path::PathBuf;
 
+use deno_core::error::custom_error;
 use deno_core::error::generic_error;
 use deno_core::error::type_error;
 use deno_core::error::AnyError;
@@ -25,7 +26,6 @@ pub fn err_invalid_module_specifier(
   type_error(msg)
 }
 
-#[allow(unused)]
 pub fn err_invalid_package_config(
   path: &str,
   maybe_base: Option<String>,
@@ -51,6 +51,64 @@ pub fn err_module_not_found(path: &str, base: &str, typ: &str) -> AnyError {
   ))
 }
 
+pub fn err_cjs_module_not_found(path: &str) -> AnyError {
+  custom_error(
+    "NotFound",
+    format!("[MODULE_NOT_FOUND] Cannot find module '{path}'"),
+  )
+}
+
+/// `ERR_INVALID_PACKAGE_TARGET`, kept as its own type so callers can tell an
+/// invalid `exports`/`imports` target apart from other resolution failures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidPackageTargetError {
+  pub pkg_path: String,
+  pub key: String,
+  pub target: String,
+  pub is_import: bool,
+  pub maybe_referrer: Option<String>,
+}
+
+impl std::fmt::Display for InvalidPackageTargetError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let rel_error = !self.is_import
+      && !self.target.is_empty()
+      && !self.target.starts_with("./");
+    let pkg_json_path = PathBuf::from(&self.pkg_path).join("package.json");
+
+    write!(f, "[ERR_INVALID_PACKAGE_TARGET]")?;
+    // "imports" keys always start with "#", so only "exports" has a main
+    if self.key == "." && !self.is_import {
+      write!(
+        f,
+        " Invalid \"exports\" main target {} defined in the package config {}",
+        self.target,
+        pkg_json_path.display()
+      )?;
+    } else {
+      let ie = if self.is_import { "imports" } else { "exports" };
+      write!(
+        f,
+        " Invalid \"{}\" target {} defined for '{}' in the package config {}",
+        ie,
+        self.target,
+        self.key,
+        pkg_json_path.display()
+      )?;
+    }
+
+    if let Some(base) = &self.maybe_referrer {
+      write!(f, " imported from {base}")?;
+    }
+    if rel_error {
+      write!(f, "; target must start with \"./\"")?;
+    }
+    Ok(())
+  }
+}
+
+impl std::error::Error for InvalidPackageTargetError {}
+
 pub fn err_invalid_package_target(
   pkg_path: &str,
   key: &str,
@@ -58,77 +116,74 @@ pub fn err_invalid_package_target(
   is_import: bool,
   maybe_referrer: Option<String>,
 ) -> AnyError {
-  let rel_error = !is_import && !target.is_empty() && !target.starts_with("./");
-  let mut msg = "[ERR_INVALID_PACKAGE_TARGET]".to_string();
-  let pkg_json_path = PathBuf::from(pkg_path).join("package.json");
-
-  if key == "." {
-    assert!(!is_import);
-    msg = format!(
-      "{} Invalid \"exports\" main target {} defined in the package config {}",
-      msg,
-      target,
-      pkg_json_path.display()
-    )
-  } else {
-    let ie = if is_import { "imports" } else { "exports" };
-    msg = format!(
-      "{} Invalid \"{}\" target {} defined for '{}' in the package config {}",
-      msg,
-      ie,
-      target,
-      key,
-      pkg_json_path.display()
-    )
-  };
-
-  if let Some(base) = maybe_referrer {
-    msg = format!("{msg} imported from {base}");
-  };
-  if rel_error {
-    msg = format!("{msg}; target must start with \"./\"");
+  InvalidPackageTargetError {
+    pkg_path: pkg_path.to_string(),
+    key: key.to_string(),
+    target: target.to_string(),
+    is_import,
+    maybe_referrer,
   }
+  .into()
+}
 
-  generic_error(msg)
+/// `ERR_PACKAGE_PATH_NOT_EXPORTED`, kept as its own type so callers can tell
+/// a subpath missing from `exports` apart from other resolution failures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackagePathNotExportedError {
+  pub pkg_path: String,
+  pub subpath: String,
+  pub maybe_referrer: Option<String>,
+  pub mode: NodeResolutionMode,
 }
 
-pub fn err_package_path_not_exported(
-  mut pkg_path: String,
-  subpath: &str,
-  maybe_referrer: Option<String>,
-  mode: NodeResolutionMode,
-) -> AnyError {
-  let mut msg = "[ERR_PACKAGE_PATH_NOT_EXPORTED]".to_string();
+impl std::fmt::Display for PackagePathNotExportedError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let separator = if cfg!(windows) { '\\' } else { '/' };
+    let mut pkg_path = self.pkg_path.clone();
+    if !pkg_path.ends_with(separator) {
+      pkg_path.push(separator);
+    }
 
-  #[cfg(windows)]
-  {
-    if !pkg_path.ends_with('\\') {
-      pkg_path.push('\\');
+    let types_msg = match self.mode {
+      NodeResolutionMode::Execution => "",
+      NodeResolutionMode::Types => " for types",
+    };
+    write!(f, "[ERR_PACKAGE_PATH_NOT_EXPORTED]")?;
+    if self.subpath == "." {
+      write!(
+        f,
+        " No \"exports\" main defined{types_msg} in '{pkg_path}package.json'"
+      )?;
+    } else {
+      write!(
+        f,
+        " Package subpath '{}' is not defined{types_msg} by \"exports\" in '{pkg_path}package.json'",
+        self.subpath
+      )?;
     }
-  }
-  #[cfg(not(windows))]
-  {
-    if !pkg_path.ends_with('/') {
-      pkg_path.push('/');
+
+    if let Some(referrer) = &self.maybe_referrer {
+      write!(f, " imported from '{referrer}'")?;
     }
+    Ok(())
   }
+}
 
-  let types_msg = match mode {
-    NodeResolutionMode::Execution => String::new(),
-    NodeResolutionMode::Types => " for types".to_string(),
-  };
-  if subpath == "." {
-    msg =
-      format!("{msg} No \"exports\" main defined{types_msg} in '{pkg_path}package.json'");
-  } else {
-    msg = format!("{msg} Package subpath '{subpath}' is not defined{types_msg} by \"exports\" in '{pkg_path}package.json'");
-  };
+impl std::error::Error for PackagePathNotExportedError {}
 
-  if let Some(referrer) = maybe_referrer {
-    msg = format!("{msg} imported from '{referrer}'");
+pub fn err_package_path_not_exported(
+  pkg_path: String,
+  subpath: &str,
+  maybe_referrer: Option<String>,
+  mode: NodeResolutionMode,
+) -> AnyError {
+  PackagePathNotExportedError {
+    pkg_path,
+    subpath: subpath.to_string(),
+    maybe_referrer,
+    mode,
   }
-
-  generic_error(msg)
+  .into()
 }
 
 pub fn err_package_import_not_defined(
@@ -150,6 +205,26 @@ pub fn err_package_import_not_defined(
   type_error(msg)
 }
 
+pub fn err_access_denied(path: &str, err: AnyError) -> AnyError {
+  custom_error(
+    "PermissionDenied",
+    format!("[EACCES] Permission denied, open '{path}': {err}"),
+  )
+}
+
+/// Raised with the `ERR_REQUIRE_ESM` class, which the `require` polyfill
+/// builds into an `Error` with that `code`.
+pub fn err_require_esm(filename: &str, maybe_parent: Option<&str>) -> AnyError {
+  let mut msg = format!("require() of ES Module {filename}");
+
+  if let Some(parent) = maybe_parent {
+    msg = format!("{msg} from {parent}");
+  }
+
+  msg = format!("{msg} not supported. Instead change the require to a dynamic import() which is available in all CommonJS modules.");
+  custom_error("ERR_REQUIRE_ESM", msg)
+}
+
 pub fn err_unsupported_dir_import(path: &str, base: &str) -> AnyError {
   generic_error(format!("[ERR_UNSUPPORTED_DIR_IMPORT] Directory import '{path}' is not supported resolving ES modules imported from {base}"))
 }
@@ -196,4 +271,20 @@ mod test {
       format!("[ERR_PACKAGE_PATH_NOT_EXPORTED] No \"exports\" main defined for types in 'test_path{separator_char}package.json'")
     );
   }
+
+  #[test]
+  fn invalid_package_target_display_is_total() {
+    let separator_char = if cfg!(windows) { '\\' } else { '/' };
+    assert_eq!(
+      err_invalid_package_target("test_path", ".", "main.js", false, None)
+        .to_string(),
+      format!("[ERR_INVALID_PACKAGE_TARGET] Invalid \"exports\" main target main.js defined in the package config test_path{separator_char}package.json; target must start with \"./\"")
+    );
+    // a malformed "." key in "imports" formats instead of panicking
+    assert_eq!(
+      err_invalid_package_target("test_path", ".", "main.js", true, None)
+        .to_string(),
+      format!("[ERR_INVALID_PACKAGE_TARGET] Invalid \"imports\" target main.js defined for '.' in the package config test_path{separator_char}package.json")
+    );
+  }
 }
@@ -6,6 +6,7 @@
 use std::collections::HashSet;
 use std::path::Path;
 use std::path::PathBuf;
+use std::rc::Rc;
 
 use deno_core::error::AnyError;
 use deno_core::located_script_name;
@@ -31,6 +32,8 @@ mod package_json;
 mod path;
 mod polyfill;
 mod resolution;
+#[cfg(test)]
+mod test_util;
 
 pub use deno_config::package_json::PackageJson;
 pub use ops::ipc::ChildPipeFd;
@@ -44,6 +47,8 @@ pub use package_json::load_pkg_json;
 pub use package_json::PackageJsonThreadLocalCache;
 pub use path::PathClean;
 pub use polyfill::is_builtin_node_module;
+pub use polyfill::BuiltinNodeModules;
+pub use polyfill::NodeModulePolyfill;
 pub use polyfill::SUPPORTED_BUILTIN_NODE_MODULES;
 pub use polyfill::SUPPORTED_BUILTIN_NODE_MODULES_WITH_PREFIX;
 pub use resolution::parse_npm_pkg_name;
@@ -149,6 +154,23 @@ impl NodePermissions for deno_permissions::PermissionsContainer {
   }
 }
 
+/// Callback invoked with the absolute path of each module a `require` call
+/// resolves to, which lets embedders trace the module graph.
+pub type ResolutionListener = Rc<dyn Fn(&Path)>;
+
+/// Embedder settings for the extension. Each `None` keeps Node's default.
+#[derive(Clone, Default)]
+pub struct Options {
+  pub resolution_listener: Option<ResolutionListener>,
+  /// Directory names searched for packages instead of `node_modules`.
+  pub modules_dir_names: Option<Vec<String>>,
+  /// Extensions `require` probes, in order, instead of Node's.
+  pub resolvable_extensions: Option<Vec<String>>,
+  /// Environment variables readable without an env permission on top of
+  /// `NODE_ENV_VAR_ALLOWLIST`.
+  pub extra_env_var_allowlist: Option<Vec<String>>,
+}
+
 #[allow(clippy::disallowed_types)]
 pub type NpmResolverRc = deno_fs::sync::MaybeArc<dyn NpmResolver>;
 
@@ -190,6 +212,27 @@ pub trait NpmResolver: std::fmt::Debug + MaybeSend + MaybeSync {
     self.in_npm_package(&specifier)
   }
 
+  /// Resolves the root folder of the npm package that contains `path`.
+  ///
+  /// The default implementation uses the closest `node_modules` directory,
+  /// which works for nested installs but not for a global package cache.
+  fn resolve_package_folder_from_path(
+    &self,
+    path: &Path,
+  ) -> Result<Option<PathBuf>, AnyError> {
+    Ok(package_folder_in_node_modules(path))
+  }
+
+  /// Gets the path of the package.json of the npm package that `path`
+  /// belongs to.
+  fn package_json_for(&self, path: &Path) -> Result<Option<PathBuf>, AnyError> {
+    Ok(
+      self
+        .resolve_package_folder_from_path(path)?
+        .map(|folder| folder.join("package.json")),
+    )
+  }
+
   fn ensure_read_permission(
     &self,
     permissions: &mut dyn NodePermissions,
@@ -197,6 +240,26 @@ pub trait NpmResolver: std::fmt::Debug + MaybeSend + MaybeSync {
   ) -> Result<(), AnyError>;
 }
 
+/// Gets the package folder directly inside the last `node_modules` of
+/// `path`, such as `/a/node_modules/@scope/pkg` for
+/// `/a/node_modules/@scope/pkg/lib/index.js`.
+fn package_folder_in_node_modules(path: &Path) -> Option<PathBuf> {
+  let components = path.components().collect::<Vec<_>>();
+  let index = components
+    .iter()
+    .rposition(|component| component.as_os_str() == "node_modules")?;
+  let name = components.get(index + 1)?;
+  let name_len = if name.as_os_str().to_string_lossy().starts_with('@') {
+    2
+  } else {
+    1
+  };
+  if components.len() < index + 1 + name_len {
+    return None;
+  }
+  Some(components[..index + 1 + name_len].iter().collect())
+}
+
 pub static NODE_ENV_VAR_ALLOWLIST: Lazy<HashSet<String>> = Lazy::new(|| {
   // The full list of environment variables supported by Node.js is available
   // at https://nodejs.org/api/cli.html#environment-variables
@@ -206,6 +269,23 @@ pub static NODE_ENV_VAR_ALLOWLIST: Lazy<HashSet<String>> = Lazy::new(|| {
   set
 });
 
+/// The environment variables that can be read without an env permission.
+/// Starts out as `NODE_ENV_VAR_ALLOWLIST` plus any extras provided at init.
+#[derive(Debug, Clone)]
+pub struct EnvVarAllowlist(pub HashSet<String>);
+
+impl Default for EnvVarAllowlist {
+  fn default() -> Self {
+    Self(NODE_ENV_VAR_ALLOWLIST.clone())
+  }
+}
+
+impl EnvVarAllowlist {
+  pub fn contains(&self, name: &str) -> bool {
+    self.0.contains(name)
+  }
+}
+
 #[op2]
 #[string]
 fn op_node_build_os() -> String {
@@ -243,6 +323,7 @@ deno_core::extension!(deno_node,
 
     ops::buffer::op_is_ascii,
     ops::buffer::op_is_utf8,
+    ops::buffer::op_node_buffer_transcode,
     ops::crypto::op_node_create_decipheriv,
     ops::crypto::op_node_cipheriv_encrypt,
     ops::crypto::op_node_cipheriv_final,
@@ -316,9 +397,23 @@ deno_core::extension!(deno_node,
     ops::fs::op_node_cp_sync<P>,
     ops::fs::op_node_cp<P>,
     ops::fs::op_node_statfs<P>,
+    ops::fs::op_node_fs_stat<P>,
+    ops::fs::op_node_fs_lstat<P>,
+    ops::fs::op_node_fs_realpath<P>,
+    ops::fs::op_node_fs_realpath_async<P>,
+    ops::fs::op_node_copy_file<P>,
+    ops::fs::op_node_fs_access<P>,
+    ops::fs::op_node_fs_access_async<P>,
+    ops::fs::op_node_readdir<P>,
+    ops::fs::op_node_fs_watch<P>,
+    ops::fs::op_node_fs_watch_next,
     ops::winerror::op_node_sys_to_uv_error,
     ops::v8::op_v8_cached_data_version_tag,
     ops::v8::op_v8_get_heap_statistics,
+    ops::v8::op_v8_get_heap_space_statistics,
+    ops::v8::op_v8_get_heap_code_statistics,
+    ops::v8::op_v8_get_heap_snapshot,
+    ops::v8::op_v8_write_heap_snapshot<P>,
     ops::vm::op_vm_create_script,
     ops::vm::op_vm_create_context,
     ops::vm::op_vm_script_run_in_context,
@@ -365,30 +460,61 @@ deno_core::extension!(deno_node,
     ops::os::op_geteuid<P>,
     ops::os::op_cpus<P>,
     ops::os::op_homedir<P>,
+    ops::perf_hooks::op_node_event_loop_utilization,
+    ops::perf_hooks::op_node_perf_now,
     op_node_build_os,
     op_node_is_promise_rejected,
     op_npm_process_state,
     ops::require::op_require_init_paths,
     ops::require::op_require_node_module_paths<P>,
+    ops::require::op_require_node_options_preloads,
     ops::require::op_require_proxy_path,
+    ops::require::op_require_create_require_context<P>,
     ops::require::op_require_is_deno_dir_package,
     ops::require::op_require_resolve_deno_dir,
     ops::require::op_require_is_request_relative,
+    ops::require::op_require_is_request_bare,
+    ops::require::op_require_validate_specifier,
+    ops::require::op_require_builtin_module_specifier,
     ops::require::op_require_resolve_lookup_paths,
     ops::require::op_require_try_self_parent_path<P>,
     ops::require::op_require_try_self<P>,
+    ops::require::op_require_try_self_types<P>,
     ops::require::op_require_real_path<P>,
+    ops::require::op_require_real_path_if_exists<P>,
+    ops::require::op_require_cache_key<P>,
+    ops::require::op_require_package_json_for<P>,
+    ops::require::op_require_module_dirname<P>,
+    ops::require::op_require_module_exists<P>,
     ops::require::op_require_path_is_absolute,
+    ops::require::op_require_is_typescript,
+    ops::require::op_require_env_allowed,
     ops::require::op_require_path_dirname,
     ops::require::op_require_stat<P>,
-    ops::require::op_require_path_resolve,
+    ops::require::op_require_stat_batch<P>,
+    ops::require::op_require_path_resolve<P>,
     ops::require::op_require_path_basename,
     ops::require::op_require_read_file<P>,
+    ops::require::op_require_read_file_bytes<P>,
+    ops::require::op_require_cjs_named_exports<P>,
     ops::require::op_require_as_file_path,
     ops::require::op_require_resolve_exports<P>,
+    ops::require::op_require_take_resolution_warnings,
+    ops::require::op_require_resolve_absolute_package<P>,
     ops::require::op_require_read_closest_package_json<P>,
+    ops::require::op_require_assert_cjs<P>,
+    ops::require::op_require_closest_package_json_path<P>,
+    ops::require::op_require_closest_tsconfig<P>,
+    ops::require::op_require_package_root<P>,
+    ops::require::op_require_package_version<P>,
+    ops::require::op_require_esm_entry<P>,
     ops::require::op_require_read_package_scope<P>,
+    ops::require::op_require_package_json_field<P>,
+    ops::require::op_require_resolve_bin<P>,
     ops::require::op_require_package_imports_resolve<P>,
+    ops::require::op_require_package_imports_resolve_esm<P>,
+    ops::require::op_require_resolve_with_conditions<P>,
+    ops::require::op_require_resolve_types<P>,
     ops::require::op_require_break_on_next_statement,
     ops::util::op_node_guess_handle_type,
     ops::worker_threads::op_worker_threads_filename<P>,
@@ -399,6 +525,10 @@ deno_core::extension!(deno_node,
     ops::ipc::op_node_ipc_read,
     ops::process::op_node_process_kill,
     ops::process::op_process_abort,
+    ops::process::op_node_process_config,
+    ops::process::op_node_spawn,
+    ops::process::op_node_spawn_wait,
+    ops::process::op_node_spawn_kill,
   ],
   esm_entry_point = "ext:deno_node/02_init.js",
   esm = [
@@ -639,12 +769,14 @@ deno_core::extension!(deno_node,
     maybe_node_resolver: Option<NodeResolverRc>,
     maybe_npm_resolver: Option<NpmResolverRc>,
     fs: deno_fs::FileSystemRc,
+    options: Options,
   },
   state = |state, options| {
     // you should provide both of these or neither
     debug_assert_eq!(options.maybe_node_resolver.is_some(), options.maybe_npm_resolver.is_some());
 
     state.put(options.fs.clone());
+    state.put(ops::perf_hooks::EventLoopStart::now());
 
     if let Some(node_resolver) = &options.maybe_node_resolver {
       state.put(node_resolver.clone());
@@ -652,6 +784,31 @@ deno_core::extension!(deno_node,
     if let Some(npm_resolver) = &options.maybe_npm_resolver {
       state.put(npm_resolver.clone());
     }
+    if let Some(listener) = &options.options.resolution_listener {
+      state.put(listener.clone());
+    }
+    state.put(match &options.options.modules_dir_names {
+      Some(dir_names) => ops::require::ModulesDirNames(dir_names.clone()),
+      None => ops::require::ModulesDirNames::default(),
+    });
+    state.put(match &options.options.resolvable_extensions {
+      Some(extensions) => ops::require::ResolvableExtensions(extensions.clone()),
+      None => ops::require::ResolvableExtensions::default(),
+    });
+    let mut env_var_allowlist = EnvVarAllowlist::default();
+    if let Some(extra) = &options.options.extra_env_var_allowlist {
+      env_var_allowlist.0.extend(extra.iter().cloned());
+    }
+    state.put(env_var_allowlist);
+    // `require()` sees the same builtins as the resolver, so polyfills are
+    // only registered with `NodeResolver::with_extra_polyfills`
+    state.put(
+      options
+        .maybe_node_resolver
+        .as_ref()
+        .map(|node_resolver| node_resolver.builtin_node_modules().clone())
+        .unwrap_or_default(),
+    );
   },
   global_template_middleware = global_template_middleware,
   global_object_middleware = global_object_middleware,
@@ -1,5 +1,7 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
 use deno_core::op2;
 
 #[op2(fast)]
@@ -11,3 +13,118 @@ pub fn op_is_ascii(#[buffer] buf: &[u8]) -> bool {
 pub fn op_is_utf8(#[buffer] buf: &[u8]) -> bool {
   std::str::from_utf8(buf).is_ok()
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TranscodeEncoding {
+  Ascii,
+  Latin1,
+  Utf8,
+  Utf16le,
+}
+
+impl TranscodeEncoding {
+  fn parse(encoding: &str) -> Option<Self> {
+    match encoding {
+      "ascii" => Some(Self::Ascii),
+      "latin1" | "binary" => Some(Self::Latin1),
+      "utf8" | "utf-8" => Some(Self::Utf8),
+      "utf16le" | "utf-16le" | "ucs2" | "ucs-2" => Some(Self::Utf16le),
+      _ => None,
+    }
+  }
+
+  fn decode(self, data: &[u8]) -> String {
+    match self {
+      Self::Ascii => data
+        .iter()
+        .map(|&b| if b.is_ascii() { b as char } else { '\u{FFFD}' })
+        .collect(),
+      Self::Latin1 => data.iter().map(|&b| b as char).collect(),
+      Self::Utf8 => String::from_utf8_lossy(data).into_owned(),
+      Self::Utf16le => char::decode_utf16(
+        data
+          .chunks_exact(2)
+          .map(|pair| u16::from_le_bytes([pair[0], pair[1]])),
+      )
+      .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+      .collect(),
+    }
+  }
+
+  /// Characters that can't be represented are replaced with "?" like ICU
+  /// does for Node.
+  fn encode(self, text: &str) -> Vec<u8> {
+    match self {
+      Self::Ascii => text
+        .chars()
+        .map(|c| if c.is_ascii() { c as u8 } else { b'?' })
+        .collect(),
+      Self::Latin1 => text
+        .chars()
+        .map(|c| if (c as u32) <= 0xFF { c as u8 } else { b'?' })
+        .collect(),
+      Self::Utf8 => text.as_bytes().to_vec(),
+      Self::Utf16le => text.encode_utf16().flat_map(u16::to_le_bytes).collect(),
+    }
+  }
+}
+
+fn transcode(data: &[u8], from: &str, to: &str) -> Result<Vec<u8>, AnyError> {
+  let (Some(from), Some(to)) =
+    (TranscodeEncoding::parse(from), TranscodeEncoding::parse(to))
+  else {
+    return Err(type_error(
+      "Unable to transcode Buffer [U_ILLEGAL_ARGUMENT_ERROR]",
+    ));
+  };
+  if from == to {
+    return Ok(data.to_vec());
+  }
+  Ok(to.encode(&from.decode(data)))
+}
+
+#[op2]
+#[buffer]
+pub fn op_node_buffer_transcode(
+  #[buffer] data: &[u8],
+  #[string] from: &str,
+  #[string] to: &str,
+) -> Result<Vec<u8>, AnyError> {
+  transcode(data, from, to)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn transcode_utf8_utf16le() {
+    let utf16le = transcode("€uro".as_bytes(), "utf8", "utf16le").unwrap();
+    assert_eq!(utf16le, vec![0xAC, 0x20, b'u', 0, b'r', 0, b'o', 0]);
+    assert_eq!(
+      transcode(&utf16le, "ucs2", "utf8").unwrap(),
+      "€uro".as_bytes()
+    );
+  }
+
+  #[test]
+  fn transcode_round_trip() {
+    let latin1 = (0..=255).collect::<Vec<u8>>();
+    let utf8 = transcode(&latin1, "latin1", "utf8").unwrap();
+    let utf16le = transcode(&utf8, "utf8", "utf16le").unwrap();
+    assert_eq!(transcode(&utf16le, "utf16le", "latin1").unwrap(), latin1);
+  }
+
+  #[test]
+  fn transcode_unrepresentable_and_unsupported() {
+    assert_eq!(
+      transcode("a€".as_bytes(), "utf8", "ascii").unwrap(),
+      b"a?".to_vec()
+    );
+    let err = transcode(b"abc", "utf8", "hex").unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "Unable to transcode Buffer [U_ILLEGAL_ARGUMENT_ERROR]"
+    );
+  }
+}
@@ -1,15 +1,34 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use std::borrow::Cow;
 use std::cell::RefCell;
 use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::time::Duration;
+use std::time::Instant;
 
 use deno_core::error::AnyError;
 use deno_core::op2;
+use deno_core::parking_lot::Mutex;
+use deno_core::unsync::spawn_blocking;
+use deno_core::AsyncRefCell;
+use deno_core::CancelFuture;
+use deno_core::CancelHandle;
 use deno_core::OpState;
+use deno_core::RcRef;
+use deno_core::Resource;
+use deno_core::ResourceId;
 use deno_fs::FileSystemRc;
+use deno_io::fs::FsStat;
+use notify::event::ModifyKind;
+use notify::Event as NotifyEvent;
+use notify::EventKind;
+use notify::RecommendedWatcher;
+use notify::RecursiveMode;
+use notify::Watcher;
 use serde::Serialize;
+use tokio::sync::mpsc;
 
 use crate::NodePermissions;
 
@@ -80,6 +99,539 @@ where
   Ok(())
 }
 
+/// The fields of Node's `fs.Stats`. The bigint variant uses `String` so large
+/// values (ex. inode numbers) survive the trip to JS where they're converted
+/// to `BigInt`s.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeStats<T> {
+  pub dev: T,
+  pub ino: T,
+  pub mode: T,
+  pub nlink: T,
+  pub uid: T,
+  pub gid: T,
+  pub rdev: T,
+  pub size: T,
+  pub blksize: T,
+  pub blocks: T,
+  pub atime_ms: T,
+  pub mtime_ms: T,
+  pub ctime_ms: T,
+  pub birthtime_ms: T,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub atime_ns: Option<T>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub mtime_ns: Option<T>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub ctime_ns: Option<T>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub birthtime_ns: Option<T>,
+  pub is_file: bool,
+  pub is_directory: bool,
+  pub is_symbolic_link: bool,
+  pub is_block_device: bool,
+  pub is_character_device: bool,
+  pub is_fifo: bool,
+  pub is_socket: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum StatsObject {
+  Number(NodeStats<f64>),
+  BigInt(NodeStats<String>),
+}
+
+impl StatsObject {
+  pub fn new(stat: &FsStat, bigint: bool) -> Self {
+    let ms = |time: Option<u64>| time.unwrap_or(0);
+    // the status change time is only known on unix, elsewhere the
+    // modification time stands in for it
+    let ctime = stat.ctime.or(stat.mtime);
+    if bigint {
+      // `FsStat` only has millisecond precision, so the nanosecond times are
+      // left out rather than made up
+      Self::BigInt(NodeStats {
+        dev: stat.dev.to_string(),
+        ino: stat.ino.to_string(),
+        mode: stat.mode.to_string(),
+        nlink: stat.nlink.to_string(),
+        uid: stat.uid.to_string(),
+        gid: stat.gid.to_string(),
+        rdev: stat.rdev.to_string(),
+        size: stat.size.to_string(),
+        blksize: stat.blksize.to_string(),
+        blocks: stat.blocks.to_string(),
+        atime_ms: ms(stat.atime).to_string(),
+        mtime_ms: ms(stat.mtime).to_string(),
+        ctime_ms: ms(ctime).to_string(),
+        birthtime_ms: ms(stat.birthtime).to_string(),
+        atime_ns: None,
+        mtime_ns: None,
+        ctime_ns: None,
+        birthtime_ns: None,
+        is_file: stat.is_file,
+        is_directory: stat.is_directory,
+        is_symbolic_link: stat.is_symlink,
+        is_block_device: stat.is_block_device,
+        is_character_device: stat.is_char_device,
+        is_fifo: stat.is_fifo,
+        is_socket: stat.is_socket,
+      })
+    } else {
+      Self::Number(NodeStats {
+        dev: stat.dev as f64,
+        ino: stat.ino as f64,
+        mode: stat.mode as f64,
+        nlink: stat.nlink as f64,
+        uid: stat.uid as f64,
+        gid: stat.gid as f64,
+        rdev: stat.rdev as f64,
+        size: stat.size as f64,
+        blksize: stat.blksize as f64,
+        blocks: stat.blocks as f64,
+        atime_ms: ms(stat.atime) as f64,
+        mtime_ms: ms(stat.mtime) as f64,
+        ctime_ms: ms(ctime) as f64,
+        birthtime_ms: ms(stat.birthtime) as f64,
+        atime_ns: None,
+        mtime_ns: None,
+        ctime_ns: None,
+        birthtime_ns: None,
+        is_file: stat.is_file,
+        is_directory: stat.is_directory,
+        is_symbolic_link: stat.is_symlink,
+        is_block_device: stat.is_block_device,
+        is_character_device: stat.is_char_device,
+        is_fifo: stat.is_fifo,
+        is_socket: stat.is_socket,
+      })
+    }
+  }
+}
+
+fn node_stat<P>(
+  state: &mut OpState,
+  path: String,
+  bigint: bool,
+  lstat: bool,
+) -> Result<StatsObject, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let path = PathBuf::from(path);
+  state
+    .borrow_mut::<P>()
+    .check_read_with_api_name(&path, Some("node:fs.stat"))?;
+  let fs = state.borrow::<FileSystemRc>();
+  let stat = if lstat {
+    fs.lstat_sync(&path)?
+  } else {
+    fs.stat_sync(&path)?
+  };
+  Ok(StatsObject::new(&stat, bigint))
+}
+
+/// `fs.statSync`.
+#[op2]
+#[serde]
+pub fn op_node_fs_stat<P>(
+  state: &mut OpState,
+  #[string] path: String,
+  bigint: bool,
+) -> Result<StatsObject, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  node_stat::<P>(state, path, bigint, false)
+}
+
+/// `fs.lstatSync`, which doesn't follow a symlink at `path`.
+#[op2]
+#[serde]
+pub fn op_node_fs_lstat<P>(
+  state: &mut OpState,
+  #[string] path: String,
+  bigint: bool,
+) -> Result<StatsObject, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  node_stat::<P>(state, path, bigint, true)
+}
+
+/// `fs.realpathSync.native`. Failures keep their OS error, so the polyfill
+/// reports them with the same code as Node (ex. `ELOOP` for a symlink loop).
+#[op2]
+#[string]
+pub fn op_node_fs_realpath<P>(
+  state: &mut OpState,
+  #[string] path: String,
+) -> Result<String, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let path = PathBuf::from(path);
+  state
+    .borrow_mut::<P>()
+    .check_read_with_api_name(&path, Some("node:fs.realpath"))?;
+  let fs = state.borrow::<FileSystemRc>();
+  let resolved = fs.realpath_sync(&path)?;
+  Ok(
+    deno_core::strip_unc_prefix(resolved)
+      .to_string_lossy()
+      .into_owned(),
+  )
+}
+
+/// `fs.realpath.native`, the async version of `op_node_fs_realpath`.
+#[op2(async)]
+#[string]
+pub async fn op_node_fs_realpath_async<P>(
+  state: Rc<RefCell<OpState>>,
+  #[string] path: String,
+) -> Result<String, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let path = PathBuf::from(path);
+  let fs = {
+    let mut state = state.borrow_mut();
+    state
+      .borrow_mut::<P>()
+      .check_read_with_api_name(&path, Some("node:fs.realpath"))?;
+    state.borrow::<FileSystemRc>().clone()
+  };
+  let resolved = fs.realpath_async(path).await?;
+  Ok(
+    deno_core::strip_unc_prefix(resolved)
+      .to_string_lossy()
+      .into_owned(),
+  )
+}
+
+/// OS error codes for the failures the ops below detect themselves. They're
+/// returned as OS errors like any other failure, so the polyfills convert
+/// them to Node errors the same way.
+#[cfg(unix)]
+mod errno {
+  pub const EEXIST: i32 = libc::EEXIST;
+  pub const ENOTSUP: i32 = libc::ENOTSUP;
+  pub const EACCES: i32 = libc::EACCES;
+}
+
+#[cfg(windows)]
+mod errno {
+  use windows_sys::Win32::Foundation::ERROR_ACCESS_DENIED;
+  use windows_sys::Win32::Foundation::ERROR_FILE_EXISTS;
+  use windows_sys::Win32::Foundation::ERROR_NOT_SUPPORTED;
+
+  pub const EEXIST: i32 = ERROR_FILE_EXISTS as i32;
+  pub const ENOTSUP: i32 = ERROR_NOT_SUPPORTED as i32;
+  pub const EACCES: i32 = ERROR_ACCESS_DENIED as i32;
+}
+
+pub const COPYFILE_EXCL: u32 = 1;
+pub const COPYFILE_FICLONE: u32 = 2;
+pub const COPYFILE_FICLONE_FORCE: u32 = 4;
+
+/// Whether `src` and `dest` are the same file, compared by device and inode
+/// like libuv does. Always false where inodes aren't available.
+fn is_same_file(fs: &dyn deno_fs::FileSystem, src: &Path, dest: &Path) -> bool {
+  match (fs.stat_sync(src), fs.stat_sync(dest)) {
+    (Ok(src), Ok(dest)) => {
+      src.ino != 0 && src.dev == dest.dev && src.ino == dest.ino
+    }
+    _ => false,
+  }
+}
+
+/// Clones `src` into `dest` with `FICLONE`, sharing the underlying extents
+/// on filesystems that support it (btrfs, xfs, ...). With `create_new`,
+/// `dest` is opened with `O_EXCL`. If the clone fails after this call
+/// created `dest`, `dest` is removed again so no empty file is left behind.
+#[cfg(target_os = "linux")]
+fn reflink(
+  fs: &dyn deno_fs::FileSystem,
+  src: &Path,
+  dest: &Path,
+  create_new: bool,
+) -> deno_io::fs::FsResult<()> {
+  // _IOW(0x94, 9, int)
+  const FICLONE: libc::c_ulong = 0x40049409;
+
+  let mode = fs.stat_sync(src)?.mode;
+  let created = create_new || fs.lstat_sync(dest).is_err();
+  let src_file = fs.open_sync(src, deno_fs::OpenOptions::read(), None)?;
+  let dest_file = fs.open_sync(
+    dest,
+    deno_fs::OpenOptions::write(true, false, create_new, Some(mode)),
+    None,
+  )?;
+  let err = match (src_file.backing_fd(), dest_file.backing_fd()) {
+    // SAFETY: libc call, both fds are kept open by the files above.
+    (Some(src_fd), Some(dest_fd))
+      if unsafe { libc::ioctl(dest_fd, FICLONE as _, src_fd) } == 0 =>
+    {
+      return Ok(());
+    }
+    (Some(_), Some(_)) => std::io::Error::last_os_error(),
+    _ => std::io::Error::from(std::io::ErrorKind::Unsupported),
+  };
+  drop(dest_file);
+  if created {
+    let _ = fs.remove_sync(dest, false);
+  }
+  Err(err.into())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn reflink(
+  _fs: &dyn deno_fs::FileSystem,
+  _src: &Path,
+  _dest: &Path,
+  _create_new: bool,
+) -> deno_io::fs::FsResult<()> {
+  Err(std::io::ErrorKind::Unsupported.into())
+}
+
+fn copy_file(
+  fs: &dyn deno_fs::FileSystem,
+  src: &Path,
+  dest: &Path,
+  mode: u32,
+) -> deno_io::fs::FsResult<()> {
+  // `O_EXCL` lets the kernel report `EEXIST`, which also covers a dangling
+  // symlink at `dest` and a file created concurrently by someone else.
+  let exclusive = mode & COPYFILE_EXCL != 0;
+  // copying a file onto itself would truncate it, so there's nothing to do
+  // unless an exclusive copy has to report that `dest` exists
+  if is_same_file(fs, src, dest) {
+    return if exclusive {
+      Err(std::io::Error::from_raw_os_error(errno::EEXIST).into())
+    } else {
+      Ok(())
+    };
+  }
+  // without a clone, an exclusive `dest` is created upfront and removed
+  // again if the copy fails, so no empty file is left behind
+  let copy = || -> deno_io::fs::FsResult<()> {
+    if exclusive {
+      fs.open_sync(
+        dest,
+        deno_fs::OpenOptions::write(true, false, true, None),
+        None,
+      )?;
+    }
+    fs.copy_file_sync(src, dest).inspect_err(|_| {
+      if exclusive {
+        let _ = fs.remove_sync(dest, false);
+      }
+    })
+  };
+  // macOS already clones with `clonefile` in `copy_file_sync`
+  if cfg!(target_os = "macos")
+    || mode & (COPYFILE_FICLONE | COPYFILE_FICLONE_FORCE) == 0
+  {
+    return copy();
+  }
+  match reflink(fs, src, dest, exclusive) {
+    Ok(()) => Ok(()),
+    Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => Err(err),
+    Err(_) if mode & COPYFILE_FICLONE_FORCE != 0 => {
+      Err(std::io::Error::from_raw_os_error(errno::ENOTSUP).into())
+    }
+    // `reflink` removed `dest` again if it created it, so an exclusive
+    // `dest` is created with `O_EXCL` anew
+    Err(_) => copy(),
+  }
+}
+
+/// `fs.copyFileSync`. `mode` is a combination of the `COPYFILE_*` flags.
+/// Failures keep their OS error, so the polyfill reports them with the same
+/// code as Node.
+#[op2(fast)]
+pub fn op_node_copy_file<P>(
+  state: &mut OpState,
+  #[string] src: String,
+  #[string] dest: String,
+  mode: u32,
+) -> Result<(), AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let src = PathBuf::from(src);
+  let dest = PathBuf::from(dest);
+  let permissions = state.borrow_mut::<P>();
+  permissions.check_read_with_api_name(&src, Some("node:fs.copyFile"))?;
+  permissions.check_write_with_api_name(&dest, Some("node:fs.copyFile"))?;
+  let fs = state.borrow::<FileSystemRc>();
+  copy_file(&**fs, &src, &dest, mode)?;
+  Ok(())
+}
+
+pub const F_OK: u32 = 0;
+pub const R_OK: u32 = 4;
+pub const W_OK: u32 = 2;
+pub const X_OK: u32 = 1;
+
+/// Checks `mode` against `path`. The file is looked up through `fs`, so a
+/// virtual file system decides whether it exists. On unix, files that are
+/// on the real file system are then checked with `access(2)`, so the kernel
+/// applies the owner, group and other bits as well as ACLs and read-only
+/// mounts. Other files are checked against their permission bits, and files
+/// without a mode (ex. on Windows) are accessible once they're known to
+/// exist.
+fn access(
+  fs: &dyn deno_fs::FileSystem,
+  path: &Path,
+  mode: u32,
+) -> deno_io::fs::FsResult<()> {
+  let stat = fs.stat_sync(path)?;
+  access_mode(path, stat.mode, mode)
+}
+
+/// The part of `access` that runs once `fs` found the file, `file_mode` are
+/// its permission bits.
+fn access_mode(
+  path: &Path,
+  file_mode: u32,
+  mode: u32,
+) -> deno_io::fs::FsResult<()> {
+  let mode = mode & (R_OK | W_OK | X_OK);
+  if mode == F_OK {
+    return Ok(());
+  }
+  #[cfg(unix)]
+  {
+    use std::os::unix::ffi::OsStrExt;
+
+    let cpath = std::ffi::CString::new(path.as_os_str().as_bytes())
+      .map_err(std::io::Error::from)?;
+    // SAFETY: `cpath` is a valid nul terminated string
+    if unsafe { libc::access(cpath.as_ptr(), mode as libc::c_int) } == 0 {
+      return Ok(());
+    }
+    let err = std::io::Error::last_os_error();
+    // only `fs` knows about the file, ex. an embedded npm package
+    if err.raw_os_error() != Some(libc::ENOENT) {
+      return Err(err.into());
+    }
+  }
+  if file_mode == 0 || file_mode & mode == mode {
+    Ok(())
+  } else {
+    Err(std::io::Error::from_raw_os_error(errno::EACCES).into())
+  }
+}
+
+/// `fs.accessSync`. `mode` is a combination of `F_OK`, `R_OK`, `W_OK` and
+/// `X_OK`. Failures keep their OS error, so the polyfill reports them with
+/// the same code as Node.
+#[op2(fast)]
+pub fn op_node_fs_access<P>(
+  state: &mut OpState,
+  #[string] path: String,
+  mode: u32,
+) -> Result<(), AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let path = PathBuf::from(path);
+  let permissions = state.borrow_mut::<P>();
+  permissions.check_read_with_api_name(&path, Some("node:fs.access"))?;
+  if mode & W_OK != 0 {
+    permissions.check_write_with_api_name(&path, Some("node:fs.access"))?;
+  }
+  let fs = state.borrow::<FileSystemRc>();
+  access(&**fs, &path, mode)?;
+  Ok(())
+}
+
+/// `fs.access`, the async version of `op_node_fs_access`.
+#[op2(async)]
+pub async fn op_node_fs_access_async<P>(
+  state: Rc<RefCell<OpState>>,
+  #[string] path: String,
+  mode: u32,
+) -> Result<(), AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let path = PathBuf::from(path);
+  let fs = {
+    let mut state = state.borrow_mut();
+    let permissions = state.borrow_mut::<P>();
+    permissions.check_read_with_api_name(&path, Some("node:fs.access"))?;
+    if mode & W_OK != 0 {
+      permissions.check_write_with_api_name(&path, Some("node:fs.access"))?;
+    }
+    state.borrow::<FileSystemRc>().clone()
+  };
+  let stat = fs.stat_async(path.clone()).await?;
+  spawn_blocking(move || access_mode(&path, stat.mode, mode)).await??;
+  Ok(())
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct DirEntry {
+  pub name: String,
+  /// Only set when the entries were requested with their types.
+  #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+  pub typ: Option<&'static str>,
+}
+
+fn read_dir_entries(
+  fs: &dyn deno_fs::FileSystem,
+  path: &Path,
+  with_types: bool,
+) -> Result<Vec<DirEntry>, AnyError> {
+  let entries = fs.read_dir_sync(path)?;
+  Ok(
+    entries
+      .into_iter()
+      .map(|entry| {
+        let typ = with_types.then(|| {
+          if entry.is_symlink {
+            "symlink"
+          } else if entry.is_directory {
+            "dir"
+          } else if entry.is_file {
+            "file"
+          } else {
+            "unknown"
+          }
+        });
+        DirEntry {
+          name: entry.name,
+          typ,
+        }
+      })
+      .collect(),
+  )
+}
+
+/// Lists a directory for `fs.readdir`. With `with_types` each entry includes
+/// its type so `Dirent`s can be created without a stat per entry.
+#[op2]
+#[serde]
+pub fn op_node_readdir<P>(
+  state: &mut OpState,
+  #[string] path: String,
+  with_types: bool,
+) -> Result<Vec<DirEntry>, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let path = PathBuf::from(path);
+  state
+    .borrow_mut::<P>()
+    .check_read_with_api_name(&path, Some("node:fs.readdir"))?;
+  let fs = state.borrow::<FileSystemRc>();
+  read_dir_entries(&**fs, &path, with_types)
+}
+
 #[derive(Debug, Serialize)]
 pub struct StatFs {
   #[serde(rename = "type")]
@@ -222,3 +774,423 @@ where
     Err(anyhow!("Unsupported platform."))
   }
 }
+
+/// Identical events received within this window are reported only once.
+///
+/// Most platforms emit several notifications for a single write (ex. one
+/// for the data and one for the metadata), while Node reports one.
+const FS_WATCH_COALESCE_WINDOW: Duration = Duration::from_millis(50);
+
+/// A file system event in the shape expected by `fs.watch()` listeners.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeFsWatchEvent {
+  /// Either `"change"` or `"rename"`.
+  event_type: &'static str,
+  filename: Option<String>,
+}
+
+struct NodeFsWatcherResource {
+  #[allow(unused)]
+  watcher: RecommendedWatcher,
+  receiver: AsyncRefCell<mpsc::Receiver<Result<NodeFsWatchEvent, AnyError>>>,
+  cancel: CancelHandle,
+}
+
+impl Resource for NodeFsWatcherResource {
+  fn name(&self) -> Cow<str> {
+    "nodeFsWatcher".into()
+  }
+
+  fn close(self: Rc<Self>) {
+    self.cancel.cancel();
+  }
+}
+
+fn node_fs_watch_event_type(kind: &EventKind) -> Option<&'static str> {
+  match kind {
+    EventKind::Create(_)
+    | EventKind::Remove(_)
+    | EventKind::Modify(ModifyKind::Name(_)) => Some("rename"),
+    EventKind::Modify(_) | EventKind::Any | EventKind::Other => Some("change"),
+    EventKind::Access(_) => None,
+  }
+}
+
+/// Node reports the path relative to the watched directory, or the file
+/// name when a single file is watched.
+fn node_fs_watch_filename(watch_path: &Path, path: &Path) -> Option<String> {
+  match path.strip_prefix(watch_path) {
+    Ok(relative) if !relative.as_os_str().is_empty() => {
+      Some(relative.to_string_lossy().to_string())
+    }
+    _ => path
+      .file_name()
+      .map(|name| name.to_string_lossy().to_string()),
+  }
+}
+
+#[op2]
+#[smi]
+pub fn op_node_fs_watch<P>(
+  state: &mut OpState,
+  #[string] path: String,
+  recursive: bool,
+) -> Result<ResourceId, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let path = PathBuf::from(path);
+  state
+    .borrow_mut::<P>()
+    .check_read_with_api_name(&path, Some("node:fs.watch"))?;
+
+  let (sender, receiver) =
+    mpsc::channel::<Result<NodeFsWatchEvent, AnyError>>(16);
+  let sender = Mutex::new((sender, None::<(NodeFsWatchEvent, Instant)>));
+  let watch_path = path.clone();
+  let mut watcher: RecommendedWatcher = Watcher::new(
+    move |res: Result<NotifyEvent, notify::Error>| {
+      let mut guard = sender.lock();
+      let (sender, last_event) = &mut *guard;
+      let event = match res {
+        Ok(event) => event,
+        Err(err) => {
+          let _ = sender.try_send(Err(err.into()));
+          return;
+        }
+      };
+      let Some(event_type) = node_fs_watch_event_type(&event.kind) else {
+        return;
+      };
+      for path in &event.paths {
+        let event = NodeFsWatchEvent {
+          event_type,
+          filename: node_fs_watch_filename(&watch_path, path),
+        };
+        let now = Instant::now();
+        if let Some((last, at)) = last_event {
+          if *last == event
+            && now.duration_since(*at) < FS_WATCH_COALESCE_WINDOW
+          {
+            continue;
+          }
+        }
+        *last_event = Some((event.clone(), now));
+        // Ignore result, if send failed it means that watcher was already
+        // closed, but not all messages have been flushed.
+        let _ = sender.try_send(Ok(event));
+      }
+    },
+    Default::default(),
+  )?;
+  let recursive_mode = if recursive {
+    RecursiveMode::Recursive
+  } else {
+    RecursiveMode::NonRecursive
+  };
+  watcher.watch(&path, recursive_mode)?;
+
+  let resource = NodeFsWatcherResource {
+    watcher,
+    receiver: AsyncRefCell::new(receiver),
+    cancel: Default::default(),
+  };
+  Ok(state.resource_table.add(resource))
+}
+
+#[op2(async)]
+#[serde]
+pub async fn op_node_fs_watch_next(
+  state: Rc<RefCell<OpState>>,
+  #[smi] rid: ResourceId,
+) -> Result<Option<NodeFsWatchEvent>, AnyError> {
+  let resource = state
+    .borrow()
+    .resource_table
+    .get::<NodeFsWatcherResource>(rid)?;
+  let mut receiver = RcRef::map(&resource, |r| &r.receiver).borrow_mut().await;
+  let cancel = RcRef::map(resource, |r| &r.cancel);
+  match receiver.recv().or_cancel(cancel).await? {
+    Some(Ok(event)) => Ok(Some(event)),
+    Some(Err(err)) => Err(err),
+    None => Ok(None),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use deno_core::serde_json;
+  use deno_core::serde_json::json;
+
+  use super::*;
+
+  #[cfg(unix)]
+  #[test]
+  fn test_read_dir_entries_with_types() {
+    use deno_fs::FileSystem;
+    use deno_fs::OpenOptions;
+    use deno_fs::RealFs;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let dir = RealFs.realpath_sync(temp_dir.path()).unwrap();
+    RealFs
+      .write_file_sync(
+        &dir.join("file.txt"),
+        OpenOptions::write(true, false, false, None),
+        None,
+        b"",
+      )
+      .unwrap();
+    RealFs.mkdir_sync(&dir.join("sub"), false, 0o755).unwrap();
+    RealFs
+      .symlink_sync(&dir.join("file.txt"), &dir.join("link"), None)
+      .unwrap();
+
+    let read_sorted = |with_types| {
+      let mut entries = read_dir_entries(&RealFs, &dir, with_types).unwrap();
+      entries.sort_by(|a, b| a.name.cmp(&b.name));
+      entries
+    };
+    let entry = |name: &str, typ| DirEntry {
+      name: name.to_string(),
+      typ,
+    };
+    assert_eq!(
+      read_sorted(true),
+      vec![
+        entry("file.txt", Some("file")),
+        entry("link", Some("symlink")),
+        entry("sub", Some("dir")),
+      ]
+    );
+    assert_eq!(
+      read_sorted(false),
+      vec![
+        entry("file.txt", None),
+        entry("link", None),
+        entry("sub", None),
+      ]
+    );
+  }
+
+  fn file_stat(ino: u64) -> FsStat {
+    FsStat {
+      is_file: true,
+      is_directory: false,
+      is_symlink: false,
+      size: 12,
+      mtime: Some(1_700_000_000_123),
+      atime: Some(1_700_000_000_456),
+      birthtime: None,
+      ctime: Some(1_700_000_000_789),
+      dev: 2049,
+      ino,
+      mode: 0o100644,
+      nlink: 1,
+      uid: 1000,
+      gid: 1000,
+      rdev: 0,
+      blksize: 4096,
+      blocks: 8,
+      is_block_device: false,
+      is_char_device: false,
+      is_fifo: false,
+      is_socket: false,
+    }
+  }
+
+  #[test]
+  fn test_node_stats() {
+    let stats =
+      serde_json::to_value(StatsObject::new(&file_stat(1234), false)).unwrap();
+    assert_eq!(stats["ino"], json!(1234.0));
+    assert_eq!(stats["size"], json!(12.0));
+    assert_eq!(stats["mtimeMs"], json!(1_700_000_000_123.0));
+    assert_eq!(stats["ctimeMs"], json!(1_700_000_000_789.0));
+    assert_eq!(stats["birthtimeMs"], json!(0.0));
+    assert!(stats.get("mtimeNs").is_none());
+    // isFile() and isDirectory() can be derived from the mode and flags
+    let mode = stats["mode"].as_f64().unwrap() as u32;
+    assert_eq!(mode & 0o170000, 0o100000);
+    assert_eq!(stats["isFile"], json!(true));
+    assert_eq!(stats["isDirectory"], json!(false));
+  }
+
+  #[test]
+  fn test_node_stats_bigint() {
+    let ino = u64::MAX - 1;
+    let stats =
+      serde_json::to_value(StatsObject::new(&file_stat(ino), true)).unwrap();
+    // doesn't lose precision like an f64 would
+    assert_eq!(stats["ino"], json!("18446744073709551614"));
+    assert_eq!(stats["mode"], json!("33188"));
+    assert_eq!(stats["mtimeMs"], json!("1700000000123"));
+    assert_eq!(stats["ctimeMs"], json!("1700000000789"));
+    // only millisecond precision is available
+    assert!(stats.get("mtimeNs").is_none());
+    assert!(stats.get("ctimeNs").is_none());
+    assert_eq!(stats["isFile"], json!(true));
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_copy_file() {
+    use deno_fs::FileSystem;
+    use deno_fs::OpenOptions;
+    use deno_fs::RealFs;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let dir = RealFs.realpath_sync(temp_dir.path()).unwrap();
+    let src = dir.join("src.txt");
+    RealFs
+      .write_file_sync(
+        &src,
+        OpenOptions::write(true, false, false, None),
+        None,
+        b"hello",
+      )
+      .unwrap();
+
+    let dest = dir.join("dest.txt");
+    copy_file(&RealFs, &src, &dest, 0).unwrap();
+    assert_eq!(RealFs.read_file_sync(&dest, None).unwrap(), b"hello");
+
+    let err = copy_file(&RealFs, &src, &dest, COPYFILE_EXCL).unwrap_err();
+    assert_eq!(err.into_io_error().raw_os_error(), Some(libc::EEXIST));
+
+    // a dangling symlink still occupies `dest`
+    let link = dir.join("link.txt");
+    RealFs
+      .symlink_sync(&dir.join("missing.txt"), &link, None)
+      .unwrap();
+    let err = copy_file(&RealFs, &src, &link, COPYFILE_EXCL).unwrap_err();
+    assert_eq!(err.into_io_error().raw_os_error(), Some(libc::EEXIST));
+    assert!(!RealFs.exists_sync(&dir.join("missing.txt")));
+
+    let fresh = dir.join("fresh.txt");
+    copy_file(&RealFs, &src, &fresh, COPYFILE_EXCL).unwrap();
+    assert_eq!(RealFs.read_file_sync(&fresh, None).unwrap(), b"hello");
+
+    // copying a file onto itself leaves it untouched
+    copy_file(&RealFs, &src, &src, 0).unwrap();
+    assert_eq!(RealFs.read_file_sync(&src, None).unwrap(), b"hello");
+    let err = copy_file(&RealFs, &src, &src, COPYFILE_EXCL).unwrap_err();
+    assert_eq!(err.into_io_error().raw_os_error(), Some(libc::EEXIST));
+    assert_eq!(RealFs.read_file_sync(&src, None).unwrap(), b"hello");
+
+    // a failed exclusive copy doesn't leave an empty `dest` behind
+    let missing = dir.join("missing.txt");
+    let dest = dir.join("not-copied.txt");
+    for mode in [COPYFILE_EXCL, COPYFILE_EXCL | COPYFILE_FICLONE] {
+      assert!(copy_file(&RealFs, &missing, &dest, mode).is_err());
+      assert!(!RealFs.exists_sync(&dest));
+    }
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_access() {
+    use deno_fs::FileSystem;
+    use deno_fs::OpenOptions;
+    use deno_fs::RealFs;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let dir = RealFs.realpath_sync(temp_dir.path()).unwrap();
+    let file = dir.join("file.txt");
+    RealFs
+      .write_file_sync(
+        &file,
+        OpenOptions::write(true, false, false, Some(0o644)),
+        None,
+        b"hello",
+      )
+      .unwrap();
+    RealFs.chmod_sync(&file, 0o644).unwrap();
+
+    access(&RealFs, &file, F_OK).unwrap();
+    access(&RealFs, &file, R_OK).unwrap();
+    access(&RealFs, &file, R_OK | W_OK).unwrap();
+    let err = access(&RealFs, &file, X_OK).unwrap_err();
+    assert_eq!(err.into_io_error().raw_os_error(), Some(libc::EACCES));
+
+    let missing = dir.join("missing.txt");
+    let err = access(&RealFs, &missing, F_OK).unwrap_err();
+    assert_eq!(err.into_io_error().raw_os_error(), Some(libc::ENOENT));
+
+    let err = access(&RealFs, &file.join("child"), F_OK).unwrap_err();
+    assert_eq!(err.into_io_error().raw_os_error(), Some(libc::ENOTDIR));
+
+    // the owner class applies to the owner even when the group may read
+    RealFs.chmod_sync(&file, 0o040).unwrap();
+    // SAFETY: libc call, geteuid can't fail
+    if unsafe { libc::geteuid() } != 0 {
+      let err = access(&RealFs, &file, R_OK).unwrap_err();
+      assert_eq!(err.into_io_error().raw_os_error(), Some(libc::EACCES));
+    }
+    RealFs.chmod_sync(&file, 0o644).unwrap();
+  }
+
+  #[test]
+  fn test_access_virtual_fs() {
+    let fs = deno_fs::InMemoryFs::default();
+    let file = PathBuf::from("/virtual/node_modules/pkg/index.js");
+    fs.setup_text_files(vec![(
+      file.to_string_lossy().to_string(),
+      "module.exports = 1;".to_string(),
+    )]);
+
+    // the file only exists in `fs`
+    access(&fs, &file, F_OK).unwrap();
+    access(&fs, &file, R_OK).unwrap();
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    for missing in [file.with_file_name("missing.js"), temp_dir.path().into()] {
+      let err = access(&fs, &missing, R_OK).unwrap_err();
+      assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    }
+  }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn test_copy_file_ficlone() {
+    use deno_fs::FileSystem;
+    use deno_fs::OpenOptions;
+    use deno_fs::RealFs;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let dir = RealFs.realpath_sync(temp_dir.path()).unwrap();
+    let src = dir.join("src.txt");
+    RealFs
+      .write_file_sync(
+        &src,
+        OpenOptions::write(true, false, false, None),
+        None,
+        b"hello",
+      )
+      .unwrap();
+
+    // falls back to a regular copy when the filesystem can't clone
+    let dest = dir.join("dest.txt");
+    copy_file(&RealFs, &src, &dest, COPYFILE_FICLONE).unwrap();
+    assert_eq!(RealFs.read_file_sync(&dest, None).unwrap(), b"hello");
+
+    // copying a file onto itself leaves it untouched
+    for mode in [COPYFILE_FICLONE, COPYFILE_FICLONE_FORCE] {
+      copy_file(&RealFs, &src, &src, mode).unwrap();
+      assert_eq!(RealFs.read_file_sync(&src, None).unwrap(), b"hello");
+    }
+
+    // a forced clone that fails leaves no empty destination behind
+    let forced = dir.join("forced.txt");
+    match copy_file(&RealFs, &src, &forced, COPYFILE_FICLONE_FORCE) {
+      Ok(()) => {
+        assert_eq!(RealFs.read_file_sync(&forced, None).unwrap(), b"hello")
+      }
+      Err(err) => {
+        assert_eq!(err.into_io_error().raw_os_error(), Some(libc::ENOTSUP));
+        assert!(!RealFs.exists_sync(&forced));
+      }
+    }
+  }
+}
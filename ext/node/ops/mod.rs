@@ -9,6 +9,7 @@ pub mod http2;
 pub mod idna;
 pub mod ipc;
 pub mod os;
+pub mod perf_hooks;
 pub mod process;
 pub mod require;
 pub mod util;
@@ -0,0 +1,176 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::time::Duration;
+use std::time::Instant;
+
+use deno_core::op2;
+use deno_core::OpState;
+use deno_permissions::PermissionsContainer;
+use serde::Serialize;
+
+/// Timing information captured when the event loop of a runtime starts.
+///
+/// There is no instrumentation of the time the event loop spends waiting for
+/// events, so the CPU time of the thread running the event loop is used as
+/// the active time. The thread doesn't consume CPU time while it's blocked
+/// waiting for I/O or timers, which makes this a close approximation.
+pub struct EventLoopStart {
+  time: Instant,
+  cpu_time: Duration,
+}
+
+impl EventLoopStart {
+  pub fn now() -> Self {
+    Self {
+      time: Instant::now(),
+      cpu_time: thread_cpu_time(),
+    }
+  }
+}
+
+/// Idle and active times are in milliseconds.
+#[derive(Serialize, Debug)]
+pub struct EventLoopUtilization {
+  idle: f64,
+  active: f64,
+  utilization: f64,
+}
+
+#[cfg(unix)]
+fn thread_cpu_time() -> Duration {
+  let mut ts = libc::timespec {
+    tv_sec: 0,
+    tv_nsec: 0,
+  };
+  // SAFETY: FFI call to libc with a valid pointer
+  if unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut ts) } != 0
+  {
+    return Duration::ZERO;
+  }
+  Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32)
+}
+
+#[cfg(windows)]
+fn thread_cpu_time() -> Duration {
+  use winapi::shared::minwindef::FILETIME;
+  use winapi::um::processthreadsapi::GetCurrentThread;
+  use winapi::um::processthreadsapi::GetThreadTimes;
+
+  let empty = || FILETIME {
+    dwLowDateTime: 0,
+    dwHighDateTime: 0,
+  };
+  let (mut creation, mut exit, mut kernel, mut user) =
+    (empty(), empty(), empty(), empty());
+  // SAFETY: FFI call to winapi with valid pointers
+  let ok = unsafe {
+    GetThreadTimes(
+      GetCurrentThread(),
+      &mut creation,
+      &mut exit,
+      &mut kernel,
+      &mut user,
+    )
+  };
+  if ok == 0 {
+    return Duration::ZERO;
+  }
+  // FILETIME is in 100 nanosecond intervals
+  let to_nanos = |time: FILETIME| {
+    (((time.dwHighDateTime as u64) << 32) | time.dwLowDateTime as u64) * 100
+  };
+  Duration::from_nanos(to_nanos(kernel) + to_nanos(user))
+}
+
+fn event_loop_utilization(start: &EventLoopStart) -> EventLoopUtilization {
+  let elapsed = start.time.elapsed();
+  let active = thread_cpu_time()
+    .saturating_sub(start.cpu_time)
+    .min(elapsed);
+  let idle = elapsed - active;
+  let utilization = if elapsed.is_zero() {
+    0.0
+  } else {
+    active.as_secs_f64() / elapsed.as_secs_f64()
+  };
+  EventLoopUtilization {
+    idle: idle.as_secs_f64() * 1000.0,
+    active: active.as_secs_f64() * 1000.0,
+    utilization,
+  }
+}
+
+/// Returns the cumulative event loop utilization since the event loop
+/// started. Deltas between readings are computed in JS.
+#[op2]
+#[serde]
+pub fn op_node_event_loop_utilization(
+  state: &mut OpState,
+) -> EventLoopUtilization {
+  let start = state.borrow::<EventLoopStart>();
+  event_loop_utilization(start)
+}
+
+/// Gets the time origin used by `performance.now()`. deno_web stores it in
+/// the state as an `Instant` when the runtime is created.
+fn time_origin(state: &OpState) -> Instant {
+  match state.try_borrow::<Instant>() {
+    Some(time_origin) => *time_origin,
+    None => state.borrow::<EventLoopStart>().time,
+  }
+}
+
+fn elapsed_ms(time_origin: Instant) -> f64 {
+  time_origin.elapsed().as_secs_f64() * 1000.0
+}
+
+/// Like `performance.now()`, the time is rounded down to 2ms unless high
+/// resolution time was allowed.
+fn now_ms(state: &mut OpState) -> f64 {
+  let now = elapsed_ms(time_origin(state));
+  if state.borrow_mut::<PermissionsContainer>().allow_hrtime() {
+    now
+  } else {
+    now - now % 2.0
+  }
+}
+
+/// Returns the time in milliseconds since the time origin.
+#[op2(fast)]
+pub fn op_node_perf_now(state: &mut OpState) -> f64 {
+  now_ms(state)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_event_loop_utilization() {
+    let start = EventLoopStart::now();
+    let before = event_loop_utilization(&start);
+    assert!((0.0..=1.0).contains(&before.utilization));
+
+    // block the thread for a bit
+    let busy_start = Instant::now();
+    let mut value = 0u64;
+    while busy_start.elapsed() < Duration::from_millis(50) {
+      value = std::hint::black_box(value.wrapping_add(1));
+    }
+
+    let after = event_loop_utilization(&start);
+    assert!((0.0..=1.0).contains(&after.utilization));
+    assert!(after.active > before.active);
+    assert!(after.idle >= 0.0);
+  }
+
+  #[test]
+  fn test_elapsed_ms() {
+    let time_origin = Instant::now();
+    let start = elapsed_ms(time_origin);
+    std::thread::sleep(Duration::from_millis(50));
+    let end = elapsed_ms(time_origin);
+    assert!(end - start >= 50.0, "{}", end - start);
+    assert!(end - start < 1000.0, "{}", end - start);
+  }
+}
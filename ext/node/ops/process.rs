@@ -1,9 +1,27 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::process::Stdio;
+use std::rc::Rc;
+
+use deno_core::error::type_error;
 use deno_core::error::AnyError;
 use deno_core::op2;
+use deno_core::serde_json::json;
+use deno_core::serde_json::Value;
+use deno_core::AsyncRefCell;
 use deno_core::OpState;
+use deno_core::RcRef;
+use deno_core::Resource;
+use deno_core::ResourceId;
+use deno_io::fs::FileResource;
+use deno_io::ChildStderrResource;
+use deno_io::ChildStdinResource;
+use deno_io::ChildStdoutResource;
 use deno_permissions::PermissionsContainer;
+use serde::Deserialize;
+use serde::Serialize;
 
 #[cfg(unix)]
 fn kill(pid: i32, sig: i32) -> i32 {
@@ -62,3 +80,394 @@ pub fn op_node_process_kill(
 pub fn op_process_abort() {
   std::process::abort();
 }
+
+/// Node's name for the architecture this was built for.
+fn node_arch() -> &'static str {
+  match std::env::consts::ARCH {
+    "x86_64" => "x64",
+    "x86" => "ia32",
+    "aarch64" => "arm64",
+    "powerpc64" => "ppc64",
+    arch => arch,
+  }
+}
+
+/// A static version of Node's `process.config` describing the build. Only
+/// the commonly probed fields are included.
+fn process_config() -> Value {
+  let arch = node_arch();
+  json!({
+    "target_defaults": {
+      "cflags": [],
+      "default_configuration": "Release",
+      "defines": [],
+      "include_dirs": [],
+      "libraries": [],
+    },
+    "variables": {
+      "asan": 0,
+      "host_arch": arch,
+      "target_arch": arch,
+      "is_debug": 0,
+      "node_install_npm": "false",
+      "node_prefix": "",
+      "node_shared": "false",
+      "node_shared_openssl": "false",
+      "node_use_openssl": "true",
+      "openssl_is_fips": "false",
+      "v8_enable_i18n_support": 1,
+      "v8_enable_inspector": 1,
+    },
+  })
+}
+
+#[op2]
+#[serde]
+pub fn op_node_process_config() -> Value {
+  process_config()
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StdioKind {
+  Pipe,
+  Inherit,
+  Ignore,
+}
+
+/// A single entry of the `stdio` option of `child_process.spawn`. Numbers 0
+/// to 2 refer to the parent's stdin, stdout and stderr, any other number is
+/// the resource id of a file opened by the caller.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum StdioOption {
+  Kind(StdioKind),
+  Fd(i32),
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SpawnConfig {
+  command: String,
+  #[serde(default)]
+  args: Vec<String>,
+  cwd: Option<String>,
+  /// When `None` the parent's environment is passed through to the child.
+  env: Option<Vec<(String, String)>>,
+  #[serde(default)]
+  stdio: Vec<StdioOption>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpawnResult {
+  rid: ResourceId,
+  pid: u32,
+  stdin_rid: Option<ResourceId>,
+  stdout_rid: Option<ResourceId>,
+  stderr_rid: Option<ResourceId>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpawnExitStatus {
+  code: Option<i32>,
+  signal: Option<i32>,
+}
+
+struct NodeChildResource {
+  child: AsyncRefCell<tokio::process::Child>,
+  pid: u32,
+}
+
+impl Resource for NodeChildResource {
+  fn name(&self) -> Cow<str> {
+    "nodeChild".into()
+  }
+}
+
+fn to_stdio(
+  option: StdioOption,
+  index: i32,
+  resolve_rid: &dyn Fn(ResourceId) -> Result<Stdio, AnyError>,
+) -> Result<Stdio, AnyError> {
+  Ok(match option {
+    StdioOption::Kind(StdioKind::Pipe) => Stdio::piped(),
+    StdioOption::Kind(StdioKind::Inherit) => Stdio::inherit(),
+    StdioOption::Kind(StdioKind::Ignore) => Stdio::null(),
+    StdioOption::Fd(fd) if fd == index => Stdio::inherit(),
+    StdioOption::Fd(fd) if fd < 0 => {
+      return Err(type_error(format!(
+        "Invalid file descriptor {fd} passed as stdio[{index}]"
+      )));
+    }
+    StdioOption::Fd(rid) if rid > 2 => resolve_rid(rid as ResourceId)?,
+    #[cfg(unix)]
+    StdioOption::Fd(fd) => {
+      use std::os::fd::FromRawFd;
+      // SAFETY: FFI call to libc
+      let fd = unsafe { libc::dup(fd) };
+      if fd < 0 {
+        return Err(std::io::Error::last_os_error().into());
+      }
+      // SAFETY: `fd` was just duplicated and is exclusively owned here
+      unsafe { Stdio::from_raw_fd(fd) }
+    }
+    #[cfg(not(unix))]
+    StdioOption::Fd(fd) => resolve_rid(fd as ResourceId)?,
+  })
+}
+
+/// Builds the command for `child_process.spawn`. When no `env` is provided
+/// the parent's environment is passed through. Numeric stdio entries above 2
+/// are turned into a `Stdio` by `resolve_rid`.
+fn create_spawn_command(
+  config: SpawnConfig,
+  resolve_rid: &dyn Fn(ResourceId) -> Result<Stdio, AnyError>,
+) -> Result<std::process::Command, AnyError> {
+  if config.stdio.len() > 3 {
+    return Err(type_error(
+      "Only stdin, stdout and stderr can be configured in stdio",
+    ));
+  }
+  let mut command = std::process::Command::new(&config.command);
+  command.args(&config.args);
+
+  if let Some(cwd) = config.cwd {
+    command.current_dir(cwd);
+  }
+
+  if let Some(env) = config.env {
+    command.env_clear();
+    command.envs(env);
+  }
+
+  let stdio = |index: usize| {
+    let option = config
+      .stdio
+      .get(index)
+      .copied()
+      .unwrap_or(StdioOption::Kind(StdioKind::Pipe));
+    to_stdio(option, index as i32, resolve_rid)
+  };
+  command.stdin(stdio(0)?);
+  command.stdout(stdio(1)?);
+  command.stderr(stdio(2)?);
+
+  Ok(command)
+}
+
+#[op2]
+#[serde]
+pub fn op_node_spawn(
+  state: &mut OpState,
+  #[serde] config: SpawnConfig,
+) -> Result<SpawnResult, AnyError> {
+  let program = config.command.clone();
+  state
+    .borrow_mut::<PermissionsContainer>()
+    .check_run(&program, "child_process.spawn")?;
+  let command = {
+    let state: &OpState = state;
+    create_spawn_command(config, &|rid| {
+      FileResource::with_file(state, rid, |file| Ok(file.as_stdio()?))
+    })?
+  };
+
+  let mut command = tokio::process::Command::from(command);
+  command.kill_on_drop(true);
+  let mut child = command.spawn().map_err(|err| {
+    AnyError::from(err).context(format!("Failed to spawn '{program}'"))
+  })?;
+  let pid = child.id().expect("Process ID should be set.");
+
+  let stdin_rid = child
+    .stdin
+    .take()
+    .map(|stdin| state.resource_table.add(ChildStdinResource::from(stdin)));
+  let stdout_rid = child
+    .stdout
+    .take()
+    .map(|stdout| state.resource_table.add(ChildStdoutResource::from(stdout)));
+  let stderr_rid = child
+    .stderr
+    .take()
+    .map(|stderr| state.resource_table.add(ChildStderrResource::from(stderr)));
+  let rid = state.resource_table.add(NodeChildResource {
+    child: AsyncRefCell::new(child),
+    pid,
+  });
+
+  Ok(SpawnResult {
+    rid,
+    pid,
+    stdin_rid,
+    stdout_rid,
+    stderr_rid,
+  })
+}
+
+#[op2(async)]
+#[serde]
+pub async fn op_node_spawn_wait(
+  state: Rc<RefCell<OpState>>,
+  #[smi] rid: ResourceId,
+) -> Result<SpawnExitStatus, AnyError> {
+  let resource = state
+    .borrow_mut()
+    .resource_table
+    .get::<NodeChildResource>(rid)?;
+  let mut child = RcRef::map(&resource, |r| &r.child).borrow_mut().await;
+  let status = child.wait().await?;
+  drop(child);
+  if let Ok(resource) = state.borrow_mut().resource_table.take_any(rid) {
+    resource.close();
+  }
+
+  #[cfg(unix)]
+  let signal = std::os::unix::process::ExitStatusExt::signal(&status);
+  #[cfg(not(unix))]
+  let signal = None;
+  Ok(SpawnExitStatus {
+    code: status.code(),
+    signal,
+  })
+}
+
+/// Sends `signo` to a child spawned by `op_node_spawn`. Unlike `process.kill`
+/// this doesn't need run permissions, as the child is owned by the caller.
+#[op2(fast)]
+pub fn op_node_spawn_kill(
+  state: &mut OpState,
+  #[smi] rid: ResourceId,
+  #[smi] signo: i32,
+) -> Result<(), AnyError> {
+  let pid = state.resource_table.get::<NodeChildResource>(rid)?.pid;
+  match kill(pid as i32, signo) {
+    0 => Ok(()),
+    errno => Err(std::io::Error::from_raw_os_error(errno).into()),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use deno_core::serde_json;
+
+  use super::*;
+
+  #[test]
+  fn process_config_shape() {
+    let config = process_config();
+    assert_eq!(
+      config["target_defaults"]["default_configuration"],
+      json!("Release")
+    );
+    let variables = config["variables"].as_object().unwrap();
+    assert_eq!(variables["node_use_openssl"], json!("true"));
+    assert_eq!(variables["v8_enable_i18n_support"], json!(1));
+    assert_eq!(variables["host_arch"], json!(node_arch()));
+  }
+
+  fn no_rids(_rid: ResourceId) -> Result<Stdio, AnyError> {
+    Err(deno_core::error::bad_resource_id())
+  }
+
+  #[cfg(unix)]
+  fn spawn_output(config: serde_json::Value) -> String {
+    let config: SpawnConfig = serde_json::from_value(config).unwrap();
+    let output = create_spawn_command(config, &no_rids)
+      .unwrap()
+      .output()
+      .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn spawn_piped_stdout() {
+    let stdout = spawn_output(json!({
+      "command": "echo",
+      "args": ["hello", "world"],
+      "stdio": ["ignore", "pipe", "inherit"],
+    }));
+    assert_eq!(stdout, "hello world\n");
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn spawn_with_shell() {
+    // the polyfill wraps the command line in `/bin/sh -c` for `shell: true`
+    let stdout = spawn_output(json!({
+      "command": "/bin/sh",
+      "args": ["-c", "echo $((1 + 2)) | tr 3 x"],
+      "stdio": ["ignore", "pipe", "inherit"],
+    }));
+    assert_eq!(stdout, "x\n");
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn spawn_with_env_and_cwd() {
+    let stdout = spawn_output(json!({
+      "command": "/bin/sh",
+      "args": ["-c", "echo $FOO; pwd"],
+      "cwd": "/",
+      "env": [["FOO", "bar"]],
+    }));
+    assert_eq!(stdout, "bar\n/\n");
+  }
+
+  #[test]
+  fn spawn_rejects_negative_fds() {
+    let config: SpawnConfig = serde_json::from_value(json!({
+      "command": "echo",
+      "stdio": ["pipe", -1, "pipe"],
+    }))
+    .unwrap();
+    let err = create_spawn_command(config, &no_rids).unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "Invalid file descriptor -1 passed as stdio[1]"
+    );
+  }
+
+  #[test]
+  fn spawn_resolves_other_numbers_as_rids() {
+    let config: SpawnConfig = serde_json::from_value(json!({
+      "command": "echo",
+      "stdio": ["pipe", 7, "pipe"],
+    }))
+    .unwrap();
+    let err = create_spawn_command(config, &no_rids).unwrap_err();
+    assert_eq!(err.to_string(), "Bad resource ID");
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn spawn_redirects_to_parent_stdio_fd() {
+    // stdout goes to the parent's stderr, so nothing is captured
+    let config: SpawnConfig = serde_json::from_value(json!({
+      "command": "echo",
+      "args": ["hello"],
+      "stdio": ["ignore", 2, "pipe"],
+    }))
+    .unwrap();
+    let output = create_spawn_command(config, &no_rids)
+      .unwrap()
+      .output()
+      .unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+  }
+
+  #[test]
+  fn spawn_rejects_extra_stdio() {
+    let config: SpawnConfig = serde_json::from_value(json!({
+      "command": "echo",
+      "stdio": ["pipe", "pipe", "pipe", "pipe"],
+    }))
+    .unwrap();
+    assert!(create_spawn_command(config, &no_rids).is_err());
+  }
+}
@@ -2,26 +2,41 @@
 
 use deno_core::anyhow::Context;
 use deno_core::error::generic_error;
+use deno_core::error::type_error;
 use deno_core::error::AnyError;
 use deno_core::normalize_path;
 use deno_core::op2;
+use deno_core::serde_json::Value;
 use deno_core::url::Url;
 use deno_core::JsRuntimeInspector;
 use deno_core::ModuleSpecifier;
 use deno_core::OpState;
 use deno_fs::FileSystemRc;
+use serde::Serialize;
 use std::cell::RefCell;
+use std::collections::HashSet;
 use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
 
+use crate::analyze::CjsAnalysis;
+use crate::analyze::CjsAnalysisExports;
+use crate::analyze::CjsCodeAnalyzer;
+use crate::analyze::CjsCodeAnalyzerRc;
+use crate::errors;
 use crate::resolution;
 use crate::resolution::NodeResolverRc;
+use crate::BuiltinNodeModules;
+use crate::EnvVarAllowlist;
 use crate::NodeModuleKind;
 use crate::NodePermissions;
+use crate::NodeResolution;
 use crate::NodeResolutionMode;
+use crate::NpmResolver;
 use crate::NpmResolverRc;
 use crate::PackageJson;
+use crate::PathClean;
+use crate::ResolutionListener;
 
 fn ensure_read_permission<P>(
   state: &mut OpState,
@@ -86,6 +101,69 @@ pub fn op_require_init_paths() -> Vec<String> {
   vec![]
 }
 
+/// Returns the modules that should be preloaded because they were passed
+/// to `--require` or `-r` in the `NODE_OPTIONS` environment variable.
+#[op2]
+#[serde]
+pub fn op_require_node_options_preloads() -> Vec<String> {
+  match std::env::var("NODE_OPTIONS") {
+    Ok(node_options) => parse_node_options_preloads(&node_options),
+    Err(_) => vec![],
+  }
+}
+
+fn parse_node_options_preloads(node_options: &str) -> Vec<String> {
+  let mut preloads = vec![];
+  let mut args = split_node_options(node_options).into_iter();
+  while let Some(arg) = args.next() {
+    if arg == "--require" || arg == "-r" {
+      if let Some(value) = args.next() {
+        preloads.push(value);
+      }
+    } else if let Some(value) = arg.strip_prefix("--require=") {
+      preloads.push(value.to_string());
+    }
+    // other options are not supported and are ignored
+  }
+  preloads
+}
+
+/// Splits `NODE_OPTIONS` into arguments the same way Node does: on
+/// whitespace, except within double quotes where a backslash escapes
+/// the following character.
+fn split_node_options(node_options: &str) -> Vec<String> {
+  let mut args = vec![];
+  let mut current: Option<String> = None;
+  let mut in_quotes = false;
+  let mut chars = node_options.chars();
+  while let Some(c) = chars.next() {
+    if in_quotes {
+      match c {
+        '"' => in_quotes = false,
+        '\\' => {
+          if let Some(next) = chars.next() {
+            current.get_or_insert_with(String::new).push(next);
+          }
+        }
+        c => current.get_or_insert_with(String::new).push(c),
+      }
+    } else if c == '"' {
+      in_quotes = true;
+      current.get_or_insert_with(String::new);
+    } else if c.is_whitespace() {
+      if let Some(arg) = current.take() {
+        args.push(arg);
+      }
+    } else {
+      current.get_or_insert_with(String::new).push(c);
+    }
+  }
+  if let Some(arg) = current {
+    args.push(arg);
+  }
+  args
+}
+
 #[op2]
 #[serde]
 pub fn op_require_node_module_paths<P>(
@@ -96,6 +174,7 @@ where
   P: NodePermissions + 'static,
 {
   let fs = state.borrow::<FileSystemRc>();
+  let dir_names = state.borrow::<ModulesDirNames>().0.clone();
   // Guarantee that "from" is absolute.
   let from_url = if from.starts_with("file:///") {
     Url::parse(&from)?
@@ -116,35 +195,71 @@ where
       let bytes = from_str.as_bytes();
       if bytes[from_str.len() - 1] == b'\\' && bytes[from_str.len() - 2] == b':'
       {
-        let p = from_str.to_owned() + "node_modules";
-        return Ok(vec![p]);
+        return Ok(
+          dir_names
+            .iter()
+            .map(|dir_name| format!("{from_str}{dir_name}"))
+            .collect(),
+        );
       }
     }
   } else {
     // Return early not only to avoid unnecessary work, but to *avoid* returning
     // an array of two items for a root: [ '//node_modules', '/node_modules' ]
     if from.to_string_lossy() == "/" {
-      return Ok(vec!["/node_modules".to_string()]);
+      return Ok(
+        dir_names
+          .iter()
+          .map(|dir_name| format!("/{dir_name}"))
+          .collect(),
+      );
     }
   }
 
-  let mut paths = vec![];
-  let mut current_path = from.as_path();
-  let mut maybe_parent = Some(current_path);
-  while let Some(parent) = maybe_parent {
-    if !parent.ends_with("node_modules") {
-      paths.push(parent.join("node_modules").to_string_lossy().to_string());
-    }
-    current_path = parent;
-    maybe_parent = current_path.parent();
+  Ok(node_module_paths(&from, &dir_names))
+}
+
+/// The names of the directories packages are installed in, which are
+/// `node_modules` unless configured otherwise when initializing the extension.
+pub struct ModulesDirNames(pub Vec<String>);
+
+impl Default for ModulesDirNames {
+  fn default() -> Self {
+    Self(vec!["node_modules".to_string()])
   }
+}
 
-  Ok(paths)
+/// Builds the list of candidate modules directories for `from` by walking up
+/// its ancestors, with one candidate per directory name at each level.
+///
+/// Note that `Path::ends_with` compares whole components, so a directory
+/// such as `node_modules_helpers` is not mistaken for a `node_modules` dir.
+fn node_module_paths(
+  from: &Path,
+  dir_names: &[impl AsRef<str>],
+) -> Vec<String> {
+  from
+    .ancestors()
+    .filter(|dir| {
+      !dir_names
+        .iter()
+        .any(|dir_name| dir.ends_with(dir_name.as_ref()))
+    })
+    .flat_map(|dir| {
+      dir_names.iter().map(|dir_name| {
+        dir.join(dir_name.as_ref()).to_string_lossy().to_string()
+      })
+    })
+    .collect()
 }
 
 #[op2]
 #[string]
 pub fn op_require_proxy_path(#[string] filename: String) -> String {
+  proxy_path(filename)
+}
+
+fn proxy_path(filename: String) -> String {
   // Allow a directory to be passed as the filename
   let trailing_slash = if cfg!(windows) {
     // Node also counts a trailing forward slash as a
@@ -163,8 +278,73 @@ pub fn op_require_proxy_path(#[string] filename: String) -> String {
   }
 }
 
+/// What `module.createRequire()` needs to resolve specifiers relative to
+/// `filename`, along with the package scope of that file.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequireContext {
+  pub filename: String,
+  pub dirname: String,
+  pub paths: Vec<String>,
+  pub package_json_path: Option<String>,
+  pub package_type: Option<String>,
+}
+
+#[op2]
+#[serde]
+pub fn op_require_create_require_context<P>(
+  state: &mut OpState,
+  #[string] filename: String,
+) -> Result<RequireContext, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let filename = PathBuf::from(proxy_path(filename));
+  if !filename.is_absolute() {
+    return Err(type_error(format!(
+      "[ERR_INVALID_ARG_VALUE] The argument 'filename' must be a file URL object, file URL string, or absolute path string. Received '{}'",
+      filename.display()
+    )));
+  }
+  // like `path.dirname()`, the root is its own directory
+  let dirname = filename.parent().unwrap_or(&filename);
+  ensure_read_permission::<P>(state, dirname)?;
+  let node_resolver = state.borrow::<NodeResolverRc>();
+  let dir_names = &state.borrow::<ModulesDirNames>().0;
+  Ok(create_require_context(node_resolver, &filename, dir_names))
+}
+
+fn create_require_context(
+  node_resolver: &resolution::NodeResolver,
+  filename: &Path,
+  dir_names: &[String],
+) -> RequireContext {
+  let filename = normalize_path(filename);
+  let dirname = filename.parent().unwrap_or(&filename);
+  // like in Node, the file and its directory don't need to exist
+  let package_json = node_resolver
+    .get_closest_package_json_from_path(&filename)
+    .ok()
+    .flatten();
+  RequireContext {
+    filename: filename.to_string_lossy().to_string(),
+    dirname: dirname.to_string_lossy().to_string(),
+    paths: node_module_paths(dirname, dir_names),
+    package_json_path: package_json
+      .as_ref()
+      .map(|pkg_json| pkg_json.path.to_string_lossy().to_string()),
+    package_type: package_json
+      .filter(|pkg_json| pkg_json.typ != "none")
+      .map(|pkg_json| pkg_json.typ.clone()),
+  }
+}
+
 #[op2(fast)]
 pub fn op_require_is_request_relative(#[string] request: String) -> bool {
+  is_request_relative(&request)
+}
+
+fn is_request_relative(request: &str) -> bool {
   if request.starts_with("./") || request.starts_with("../") || request == ".."
   {
     return true;
@@ -183,6 +363,87 @@ pub fn op_require_is_request_relative(#[string] request: String) -> bool {
   false
 }
 
+/// Whether `request` is a bare specifier, so neither relative, absolute
+/// (including Windows drive and UNC paths) nor a `node:` builtin.
+#[op2(fast)]
+pub fn op_require_is_request_bare(#[string] request: String) -> bool {
+  is_request_bare(&request)
+}
+
+fn is_request_bare(request: &str) -> bool {
+  let is_rooted = request.starts_with('/')
+    || (cfg!(windows) && request.starts_with('\\'))
+    || Path::new(request).is_absolute();
+  !(request.is_empty()
+    || request == "."
+    || is_request_relative(request)
+    || is_rooted
+    || request.starts_with("node:"))
+}
+
+/// Returns the specifier of the module implementing the builtin
+/// `module_name`, taking the polyfills registered by the embedder into
+/// account, or `None` when it isn't a builtin.
+#[op2]
+#[string]
+pub fn op_require_builtin_module_specifier(
+  state: &mut OpState,
+  #[string] module_name: String,
+) -> Option<String> {
+  let module_name = module_name.strip_prefix("node:").unwrap_or(&module_name);
+  state.borrow::<BuiltinNodeModules>().specifier(module_name)
+}
+
+/// Rejects specifiers that can never resolve, so the loader fails early
+/// with a descriptive error instead of an obscure "not found".
+#[op2]
+pub fn op_require_validate_specifier(
+  #[string] request: String,
+) -> Result<(), AnyError> {
+  validate_specifier(&request)
+}
+
+fn validate_specifier(request: &str) -> Result<(), AnyError> {
+  let reason = if request.is_empty() {
+    "must be a non-empty string"
+  } else if request.contains('\0') {
+    "must not contain null bytes"
+  } else if has_unsupported_scheme(request) {
+    "has an unsupported URL scheme"
+  } else if cfg!(windows) && has_windows_reserved_name(request) {
+    "refers to a reserved device name"
+  } else {
+    return Ok(());
+  };
+  Err(errors::err_invalid_module_specifier(request, reason, None))
+}
+
+fn has_unsupported_scheme(request: &str) -> bool {
+  match Url::parse(request) {
+    // a single letter "scheme" is a Windows drive letter
+    Ok(url) => {
+      url.scheme().len() > 1 && !matches!(url.scheme(), "file" | "node")
+    }
+    Err(_) => false,
+  }
+}
+
+/// Whether a path segment of `request` is a device name like `CON` or
+/// `LPT1`, which Windows reserves regardless of the extension.
+fn has_windows_reserved_name(request: &str) -> bool {
+  request.split(['/', '\\']).any(|segment| {
+    let stem = segment.split('.').next().unwrap_or_default().trim_end();
+    let stem = stem.to_ascii_uppercase();
+    match stem.as_str() {
+      "CON" | "PRN" | "AUX" | "NUL" => true,
+      _ => {
+        (stem.starts_with("COM") || stem.starts_with("LPT"))
+          && matches!(stem.as_bytes()[3..], [b'1'..=b'9'])
+      }
+    }
+  })
+}
+
 #[op2]
 #[string]
 pub fn op_require_resolve_deno_dir(
@@ -258,6 +519,32 @@ pub fn op_require_path_is_absolute(#[string] p: String) -> bool {
   PathBuf::from(p).is_absolute()
 }
 
+/// Extensions of files that have to be transpiled before they are executed.
+const TYPESCRIPT_EXTENSIONS: [&str; 4] = ["ts", "tsx", "mts", "cts"];
+
+fn is_typescript_file(path: &Path) -> bool {
+  path
+    .extension()
+    .and_then(|ext| ext.to_str())
+    .map(|ext| TYPESCRIPT_EXTENSIONS.contains(&ext))
+    .unwrap_or(false)
+}
+
+#[op2(fast)]
+pub fn op_require_is_typescript(#[string] filename: String) -> bool {
+  is_typescript_file(Path::new(&filename))
+}
+
+/// Whether `name` can be read from the environment without an env
+/// permission, according to the allowlist of this runtime.
+#[op2(fast)]
+pub fn op_require_env_allowed(
+  state: &mut OpState,
+  #[string] name: &str,
+) -> bool {
+  state.borrow::<EnvVarAllowlist>().contains(name)
+}
+
 #[op2(fast)]
 pub fn op_require_stat<P>(
   state: &mut OpState,
@@ -269,15 +556,123 @@ where
   let path = PathBuf::from(path);
   ensure_read_permission::<P>(state, &path)?;
   let fs = state.borrow::<FileSystemRc>();
-  if let Ok(metadata) = fs.stat_sync(&path) {
-    if metadata.is_file {
-      return Ok(0);
-    } else {
-      return Ok(1);
+  Ok(stat_kind(&**fs, &path))
+}
+
+/// Stats many paths at once, returning the same codes as `op_require_stat`
+/// for each path. Read permission is checked for every unique path, as
+/// access to a directory doesn't imply access to the entries in it.
+#[op2]
+#[serde]
+pub fn op_require_stat_batch<P>(
+  state: &mut OpState,
+  #[serde] paths: Vec<String>,
+) -> Result<Vec<i32>, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let paths = paths.into_iter().map(PathBuf::from).collect::<Vec<_>>();
+  let mut checked_paths = HashSet::new();
+  for path in &paths {
+    if checked_paths.insert(path) {
+      ensure_read_permission::<P>(state, path)?;
     }
   }
+  let fs = state.borrow::<FileSystemRc>();
+  Ok(paths.iter().map(|path| stat_kind(&**fs, path)).collect())
+}
+
+/// Returns 0 for a file, 1 for a directory and -1 when the path doesn't
+/// exist.
+fn stat_kind(fs: &dyn deno_fs::FileSystem, path: &Path) -> i32 {
+  match fs.stat_sync(path) {
+    Ok(metadata) if metadata.is_file => 0,
+    Ok(_) => 1,
+    Err(_) => -1,
+  }
+}
+
+/// The extensions probed, in order, when a request doesn't point to an
+/// existing file. Defaults to Node's order and can be configured when
+/// initializing the extension, for example to also find `.ts` files.
+pub struct ResolvableExtensions(pub Vec<String>);
+
+impl Default for ResolvableExtensions {
+  fn default() -> Self {
+    Self(vec![
+      ".js".to_string(),
+      ".json".to_string(),
+      ".node".to_string(),
+    ])
+  }
+}
+
+/// Probes `request`, then `request` with each of the extensions and finally
+/// the `index` file of the directory with each of the extensions, returning
+/// the first path that is an existing file. The configured extensions are
+/// used when none are provided. Read permission is checked for every
+/// candidate before it's probed.
+#[op2]
+#[string]
+pub fn op_require_module_exists<P>(
+  state: &mut OpState,
+  #[string] request: String,
+  #[serde] extensions: Option<Vec<String>>,
+) -> Result<Option<String>, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let path = PathBuf::from(request);
+  let extensions = match extensions {
+    Some(extensions) => extensions,
+    None => state.borrow::<ResolvableExtensions>().0.clone(),
+  };
+  let fs = state.borrow::<FileSystemRc>().clone();
+  Ok(
+    probe_module_path(&*fs, &path, &extensions, &mut |candidate| {
+      ensure_read_permission::<P>(state, candidate)
+    })?
+    .map(|p| p.to_string_lossy().to_string()),
+  )
+}
 
-  Ok(-1)
+/// Probes the candidates for `path` in order, calling `ensure_readable` on
+/// each one before it's stat-ed.
+fn probe_module_path(
+  fs: &dyn deno_fs::FileSystem,
+  path: &Path,
+  extensions: &[String],
+  ensure_readable: &mut dyn FnMut(&Path) -> Result<(), AnyError>,
+) -> Result<Option<PathBuf>, AnyError> {
+  let mut is_file = |candidate: &Path| -> Result<bool, AnyError> {
+    ensure_readable(candidate)?;
+    Ok(fs.is_file_sync(candidate))
+  };
+  if is_file(path)? {
+    return Ok(Some(path.to_path_buf()));
+  }
+  let with_extension = |path: &Path, ext: &str| {
+    let mut path = path.as_os_str().to_owned();
+    path.push(ext);
+    PathBuf::from(path)
+  };
+  for ext in extensions {
+    let candidate = with_extension(path, ext);
+    if is_file(&candidate)? {
+      return Ok(Some(candidate));
+    }
+  }
+  // the request itself was already checked above
+  if fs.is_dir_sync(path) {
+    let index = path.join("index");
+    for ext in extensions {
+      let candidate = with_extension(&index, ext);
+      if is_file(&candidate)? {
+        return Ok(Some(candidate));
+      }
+    }
+  }
+  Ok(None)
 }
 
 #[op2]
@@ -292,26 +687,207 @@ where
   let path = PathBuf::from(request);
   ensure_read_permission::<P>(state, &path)?;
   let fs = state.borrow::<FileSystemRc>();
-  let canonicalized_path =
-    deno_core::strip_unc_prefix(fs.realpath_sync(&path)?);
-  Ok(canonicalized_path.to_string_lossy().to_string())
+  let canonicalized_path = real_path(&**fs, &path)?;
+  Ok(strip_extended_length_prefix(
+    canonicalized_path.to_string_lossy().to_string(),
+  ))
+}
+
+/// Like `op_require_real_path`, but returns `None` instead of throwing when
+/// `request` doesn't exist, which is the common case when a loader probes
+/// candidate paths. Other errors (ex. permission errors) are still thrown.
+#[op2]
+#[string]
+pub fn op_require_real_path_if_exists<P>(
+  state: &mut OpState,
+  #[string] request: String,
+) -> Result<Option<String>, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let path = PathBuf::from(request);
+  ensure_read_permission::<P>(state, &path)?;
+  let fs = state.borrow::<FileSystemRc>();
+  let Some(canonicalized_path) = real_path_if_exists(&**fs, &path)? else {
+    return Ok(None);
+  };
+  Ok(Some(strip_extended_length_prefix(
+    canonicalized_path.to_string_lossy().to_string(),
+  )))
+}
+
+/// Returns the key a module at `filename` is cached under in
+/// `require.cache`, so the same module reached through a symlink or a
+/// differently cased path isn't loaded twice. This is the final filename a
+/// `require` call resolves to, so the resolution listener is notified of it
+/// here and nowhere else.
+#[op2]
+#[string]
+pub fn op_require_cache_key<P>(
+  state: &mut OpState,
+  #[string] filename: String,
+) -> Result<String, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let path = PathBuf::from(filename);
+  ensure_read_permission::<P>(state, &path)?;
+  let fs = state.borrow::<FileSystemRc>();
+  let key = cache_key(&**fs, &path)?;
+  notify_resolution_listener(state, Path::new(&key));
+  Ok(key)
+}
+
+fn cache_key(
+  fs: &dyn deno_fs::FileSystem,
+  path: &Path,
+) -> Result<String, AnyError> {
+  // the real path has the casing on disk, so differently cased paths to a
+  // file on a case insensitive filesystem share a key, while distinct files
+  // in a case sensitive directory keep their own
+  Ok(strip_extended_length_prefix(
+    real_path(fs, path)?.to_string_lossy().to_string(),
+  ))
+}
+
+/// Gets the package.json of the npm package that owns `filename`, even
+/// when it is nested in another package's `node_modules`.
+#[op2]
+#[string]
+pub fn op_require_package_json_for<P>(
+  state: &mut OpState,
+  #[string] filename: String,
+) -> Result<Option<String>, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let path = PathBuf::from(filename);
+  ensure_read_permission::<P>(state, &path)?;
+  let npm_resolver = state.borrow::<NpmResolverRc>();
+  Ok(
+    npm_resolver
+      .package_json_for(&path)?
+      .map(|path| path.to_string_lossy().to_string()),
+  )
+}
+
+/// Returns the `__dirname` for a CJS module: the directory of the file
+/// after symlinks are resolved the same way as `op_require_real_path`.
+#[op2]
+#[string]
+pub fn op_require_module_dirname<P>(
+  state: &mut OpState,
+  #[string] filename: String,
+) -> Result<String, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let path = PathBuf::from(filename);
+  ensure_read_permission::<P>(state, &path)?;
+  let fs = state.borrow::<FileSystemRc>();
+  module_dirname(&**fs, &path)
+}
+
+fn module_dirname(
+  fs: &dyn deno_fs::FileSystem,
+  filename: &Path,
+) -> Result<String, AnyError> {
+  let canonicalized_path = real_path(fs, filename)?;
+  match canonicalized_path.parent() {
+    Some(parent) => Ok(strip_extended_length_prefix(
+      parent.to_string_lossy().to_string(),
+    )),
+    None => Err(generic_error("Path doesn't have a parent")),
+  }
+}
+
+/// Canonicalizes a module path. A path that disappeared since it was found
+/// is reported as a missing module rather than a bare io error.
+fn real_path(
+  fs: &dyn deno_fs::FileSystem,
+  path: &Path,
+) -> Result<PathBuf, AnyError> {
+  fs.realpath_sync(path).map_err(|err| {
+    if err.kind() == std::io::ErrorKind::NotFound {
+      errors::err_cjs_module_not_found(&path.to_string_lossy())
+    } else {
+      err.into()
+    }
+  })
+}
+
+fn real_path_if_exists(
+  fs: &dyn deno_fs::FileSystem,
+  path: &Path,
+) -> Result<Option<PathBuf>, AnyError> {
+  match fs.realpath_sync(path) {
+    Ok(path) => Ok(Some(path)),
+    Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+    Err(err) => Err(err.into()),
+  }
+}
+
+fn notify_resolution_listener(state: &OpState, path: &Path) {
+  if let Some(listener) = state.try_borrow::<ResolutionListener>() {
+    listener(path);
+  }
+}
+
+/// Converts a Windows extended-length path to the form Node produces, so
+/// `\\?\C:\a` becomes `C:\a` and `\\?\UNC\server\share\a` becomes
+/// `\\server\share\a`. Other paths are returned as is.
+fn strip_extended_length_prefix(path: String) -> String {
+  if let Some(rest) = path.strip_prefix(r"\\?\UNC\") {
+    format!(r"\\{rest}")
+  } else if let Some(rest) = path.strip_prefix(r"\\?\") {
+    let bytes = rest.as_bytes();
+    let is_drive_path =
+      bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+    if is_drive_path {
+      rest.to_string()
+    } else {
+      path
+    }
+  } else {
+    path
+  }
 }
 
-fn path_resolve(parts: Vec<String>) -> String {
-  assert!(!parts.is_empty());
-  let mut p = PathBuf::from(&parts[0]);
-  if parts.len() > 1 {
-    for part in &parts[1..] {
-      p = p.join(part);
+/// Resolves the parts to a path like Node's `path.resolve`. The parts are
+/// processed from right to left until an absolute one is found, empty parts
+/// are skipped and the result is normalized. When none of the parts are
+/// absolute, they're resolved relative to `cwd`.
+fn path_resolve(
+  parts: &[String],
+  cwd: impl FnOnce() -> Result<PathBuf, AnyError>,
+) -> Result<String, AnyError> {
+  let start = parts.iter().rposition(|part| Path::new(part).is_absolute());
+  let mut p = match start {
+    Some(_) => PathBuf::new(),
+    None => cwd()?,
+  };
+  for part in &parts[start.unwrap_or(0)..] {
+    if !part.is_empty() {
+      p.push(part);
     }
   }
-  normalize_path(p).to_string_lossy().to_string()
+  Ok(normalize_path(p).to_string_lossy().to_string())
 }
 
 #[op2]
 #[string]
-pub fn op_require_path_resolve(#[serde] parts: Vec<String>) -> String {
-  path_resolve(parts)
+pub fn op_require_path_resolve<P>(
+  state: &mut OpState,
+  #[serde] parts: Vec<String>,
+) -> Result<String, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  path_resolve(&parts, || {
+    let cwd = state.borrow::<FileSystemRc>().cwd()?;
+    ensure_read_permission::<P>(state, &cwd)?;
+    Ok(cwd)
+  })
 }
 
 #[op2]
@@ -371,6 +947,19 @@ where
   Ok(None)
 }
 
+/// Maps a request that names the package itself (e.g. `@scope/pkg/feature`
+/// from inside `@scope/pkg`) to the subpath to look up in its "exports".
+fn self_reference_expansion(pkg_name: &str, request: &str) -> Option<String> {
+  let rest = request.strip_prefix(pkg_name)?;
+  if rest.is_empty() {
+    Some(".".to_string())
+  } else if rest.starts_with('/') {
+    Some(format!(".{rest}"))
+  } else {
+    None
+  }
+}
+
 #[op2]
 #[string]
 pub fn op_require_try_self<P>(
@@ -381,15 +970,50 @@ pub fn op_require_try_self<P>(
 where
   P: NodePermissions + 'static,
 {
-  if parent_path.is_none() {
+  let Some(parent_path) = parent_path else {
     return Ok(None);
-  }
+  };
+  let node_resolver = state.borrow::<NodeResolverRc>();
+  try_self(
+    node_resolver,
+    &parent_path,
+    &request,
+    NodeResolutionMode::Execution,
+  )
+}
 
+/// Like `op_require_try_self`, but resolves a self-reference with the
+/// "types" condition so a package importing itself gets its declarations.
+#[op2]
+#[string]
+pub fn op_require_try_self_types<P>(
+  state: &mut OpState,
+  #[string] parent_path: Option<String>,
+  #[string] request: String,
+) -> Result<Option<String>, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let Some(parent_path) = parent_path else {
+    return Ok(None);
+  };
   let node_resolver = state.borrow::<NodeResolverRc>();
+  try_self(
+    node_resolver,
+    &parent_path,
+    &request,
+    NodeResolutionMode::Types,
+  )
+}
+
+fn try_self(
+  node_resolver: &resolution::NodeResolver,
+  parent_path: &str,
+  request: &str,
+  mode: NodeResolutionMode,
+) -> Result<Option<String>, AnyError> {
   let pkg = node_resolver
-    .get_closest_package_json(
-      &Url::from_file_path(parent_path.unwrap()).unwrap(),
-    )
+    .get_closest_package_json(&Url::from_file_path(parent_path).unwrap())
     .ok()
     .flatten();
   if pkg.is_none() {
@@ -404,16 +1028,11 @@ where
     return Ok(None);
   }
 
-  let pkg_name = pkg.name.as_ref().unwrap().to_string();
-  let mut expansion = ".".to_string();
-
-  if request == pkg_name {
-    // pass
-  } else if request.starts_with(&format!("{pkg_name}/")) {
-    expansion += &request[pkg_name.len()..];
-  } else {
+  let Some(expansion) =
+    self_reference_expansion(pkg.name.as_ref().unwrap(), request)
+  else {
     return Ok(None);
-  }
+  };
 
   let referrer = deno_core::url::Url::from_file_path(&pkg.path).unwrap();
   if let Some(exports) = &pkg.exports {
@@ -424,7 +1043,7 @@ where
       &referrer,
       NodeModuleKind::Cjs,
       resolution::REQUIRE_CONDITIONS,
-      NodeResolutionMode::Execution,
+      mode,
     )?;
     Ok(Some(if r.scheme() == "file" {
       url_to_file_path_string(&r)?
@@ -451,6 +1070,75 @@ where
   Ok(fs.read_text_file_lossy_sync(&file_path, None)?)
 }
 
+/// Reads a file as bytes for content that isn't necessarily UTF-8, unlike
+/// `op_require_read_file` which replaces invalid sequences.
+#[op2]
+#[buffer]
+pub fn op_require_read_file_bytes<P>(
+  state: &mut OpState,
+  #[string] file_path: String,
+) -> Result<Vec<u8>, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let file_path = PathBuf::from(file_path);
+  ensure_read_permission::<P>(state, &file_path)?;
+  let fs = state.borrow::<FileSystemRc>();
+  Ok(fs.read_file_sync(&file_path, None)?)
+}
+
+/// Statically detects the named exports of a CommonJS file so they can be
+/// exposed when the file is imported from ESM.
+///
+/// Resolves to `{ exports: string[], reexports: string[] }`. `exports` are
+/// the names assigned by the file itself (ex. `exports.foo = ...`), while
+/// `reexports` are the specifiers of modules whose exports it forwards (ex.
+/// `module.exports = require("./other")`). Reexports are returned as
+/// written and need to be resolved and analyzed by the caller.
+///
+/// The analysis is done by the `CjsCodeAnalyzerRc` the embedder put in the
+/// op state. ES modules have no CommonJS exports, so nothing is reported
+/// for them.
+#[op2(async)]
+#[serde]
+pub async fn op_require_cjs_named_exports<P>(
+  state: Rc<RefCell<OpState>>,
+  #[string] filename: String,
+) -> Result<CjsAnalysisExports, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let file_path = PathBuf::from(filename);
+  let (analyzer, fs) = {
+    let mut state = state.borrow_mut();
+    ensure_read_permission::<P>(&mut state, &file_path)?;
+    let analyzer = state
+      .try_borrow::<CjsCodeAnalyzerRc>()
+      .cloned()
+      .ok_or_else(|| {
+        generic_error("CommonJS export analysis is not available")
+      })?;
+    (analyzer, state.borrow::<FileSystemRc>().clone())
+  };
+  cjs_named_exports(&*analyzer, &*fs, &file_path).await
+}
+
+async fn cjs_named_exports(
+  analyzer: &dyn CjsCodeAnalyzer,
+  fs: &dyn deno_fs::FileSystem,
+  file_path: &Path,
+) -> Result<CjsAnalysisExports, AnyError> {
+  let source = fs.read_text_file_lossy_sync(file_path, None)?;
+  let specifier = file_path_to_url(file_path)?;
+  match analyzer.analyze_cjs(&specifier, Some(source)).await? {
+    CjsAnalysis::Cjs(analysis) => Ok(analysis),
+    CjsAnalysis::Esm(_) => Ok(CjsAnalysisExports {
+      exports: Vec::new(),
+      reexports: Vec::new(),
+    }),
+  }
+}
+
 #[op2]
 #[string]
 pub fn op_require_as_file_path(#[string] file_or_url: String) -> String {
@@ -473,13 +1161,14 @@ pub fn op_require_resolve_exports<P>(
   #[string] name: String,
   #[string] expansion: String,
   #[string] parent_path: String,
+  #[serde] conditions: Option<Vec<String>>,
 ) -> Result<Option<String>, AnyError>
 where
   P: NodePermissions + 'static,
 {
-  let fs = state.borrow::<FileSystemRc>();
-  let npm_resolver = state.borrow::<NpmResolverRc>();
-  let node_resolver = state.borrow::<NodeResolverRc>();
+  let fs = state.borrow::<FileSystemRc>().clone();
+  let npm_resolver = state.borrow::<NpmResolverRc>().clone();
+  let node_resolver = state.borrow::<NodeResolverRc>().clone();
 
   let pkg_path = if npm_resolver
     .in_npm_package_at_file_path(&PathBuf::from(&modules_path))
@@ -488,7 +1177,7 @@ where
     modules_path
   } else {
     let original = modules_path.clone();
-    let mod_dir = path_resolve(vec![modules_path, name]);
+    let mod_dir = path_resolve(&[modules_path, name], || Ok(PathBuf::new()))?;
     if fs.is_dir_sync(Path::new(&mod_dir)) {
       mod_dir
     } else {
@@ -505,20 +1194,147 @@ where
   };
 
   let referrer = Url::from_file_path(parent_path).unwrap();
-  let r = node_resolver.package_exports_resolve(
+  let r = match &conditions {
+    Some(conditions) => {
+      let conditions =
+        conditions.iter().map(|c| c.as_str()).collect::<Vec<_>>();
+      node_resolver.package_exports_resolve_exact(
+        &pkg.path,
+        &format!(".{expansion}"),
+        exports,
+        &referrer,
+        NodeModuleKind::Cjs,
+        &conditions,
+        NodeResolutionMode::Execution,
+      )?
+    }
+    None => node_resolver.package_exports_resolve(
+      &pkg.path,
+      &format!(".{expansion}"),
+      exports,
+      &referrer,
+      NodeModuleKind::Cjs,
+      resolution::REQUIRE_CONDITIONS,
+      NodeResolutionMode::Execution,
+    )?,
+  };
+  Ok(Some(if r.scheme() == "file" {
+    let file_path = url_to_file_path(&r)?;
+    ensure_target_read_permission(
+      &*npm_resolver,
+      state.borrow_mut::<P>(),
+      &file_path,
+    )?;
+    file_path.to_string_lossy().to_string()
+  } else {
+    r.to_string()
+  }))
+}
+
+/// Takes the warnings raised while resolving, which `require()` emits with
+/// `process.emitWarning()`.
+#[op2]
+#[serde]
+pub fn op_require_take_resolution_warnings(
+  state: &mut OpState,
+) -> Vec<resolution::NodeResolutionWarning> {
+  state.borrow::<NodeResolverRc>().take_warnings()
+}
+
+/// Resolves `require("/abs/node_modules/pkg")` through the "exports" of the
+/// package directory it points to. Returns `None` when the directory isn't
+/// a package directly inside a modules directory or the package doesn't
+/// export ".", in which case its "main" or index file is loaded.
+#[op2]
+#[string]
+pub fn op_require_resolve_absolute_package<P>(
+  state: &mut OpState,
+  #[string] request: String,
+  #[string] parent_path: Option<String>,
+) -> Result<Option<String>, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let pkg_dir = PathBuf::from(request);
+  ensure_read_permission::<P>(state, &pkg_dir)?;
+  let node_resolver = state.borrow::<NodeResolverRc>();
+  let dir_names = &state.borrow::<ModulesDirNames>().0;
+  let referrer = file_path_to_url(
+    &parent_path
+      .map(PathBuf::from)
+      .unwrap_or_else(|| pkg_dir.clone()),
+  )?;
+  let maybe_path =
+    resolve_package_dir_exports(node_resolver, &pkg_dir, dir_names, &referrer)?;
+  Ok(maybe_path.map(|path| path.to_string_lossy().to_string()))
+}
+
+fn resolve_package_dir_exports(
+  node_resolver: &resolution::NodeResolver,
+  pkg_dir: &Path,
+  dir_names: &[String],
+  referrer: &Url,
+) -> Result<Option<PathBuf>, AnyError> {
+  // only the package itself (`node_modules/pkg` or `node_modules/@s/pkg`),
+  // not a subdirectory of it that happens to have a package.json
+  let mut parent = pkg_dir.parent();
+  if parent
+    .and_then(Path::file_name)
+    .is_some_and(|name| name.to_string_lossy().starts_with('@'))
+  {
+    parent = parent.and_then(Path::parent);
+  }
+  let in_modules_dir = parent.and_then(Path::file_name).is_some_and(|name| {
+    dir_names.iter().any(|dir_name| name == dir_name.as_str())
+  });
+  if !in_modules_dir {
+    return Ok(None);
+  }
+  let Some(pkg) =
+    node_resolver.load_package_json(&pkg_dir.join("package.json"))?
+  else {
+    return Ok(None);
+  };
+  let Some(exports) = &pkg.exports else {
+    return Ok(None);
+  };
+  if !exports.contains_key(".") {
+    return Ok(None);
+  }
+  let resolved = match node_resolver.package_exports_resolve(
     &pkg.path,
-    &format!(".{expansion}"),
+    ".",
     exports,
-    &referrer,
+    referrer,
     NodeModuleKind::Cjs,
     resolution::REQUIRE_CONDITIONS,
     NodeResolutionMode::Execution,
-  )?;
-  Ok(Some(if r.scheme() == "file" {
-    url_to_file_path_string(&r)?
-  } else {
-    r.to_string()
-  }))
+  ) {
+    Ok(resolved) => resolved,
+    // no target for require, so fall back to "main" like before
+    Err(err)
+      if err
+        .downcast_ref::<errors::PackagePathNotExportedError>()
+        .is_some() =>
+    {
+      return Ok(None)
+    }
+    Err(err) => return Err(err),
+  };
+  url_to_file_path(&resolved).map(Some)
+}
+
+/// Checks read permission for the target a package resolved to, so a
+/// denied read surfaces as an EACCES error rather than a module not found
+/// error once loading it fails.
+fn ensure_target_read_permission(
+  npm_resolver: &dyn NpmResolver,
+  permissions: &mut dyn NodePermissions,
+  file_path: &Path,
+) -> Result<(), AnyError> {
+  npm_resolver
+    .ensure_read_permission(permissions, file_path)
+    .map_err(|err| errors::err_access_denied(&file_path.to_string_lossy(), err))
 }
 
 #[op2]
@@ -540,6 +1356,166 @@ where
     .map(|maybe_pkg| maybe_pkg.map(|pkg| (*pkg).clone()))
 }
 
+#[op2]
+pub fn op_require_assert_cjs<P>(
+  state: &mut OpState,
+  #[string] filename: String,
+  #[string] maybe_parent_filename: Option<String>,
+) -> Result<(), AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let file_path = PathBuf::from(filename);
+  // neither `""` nor the root can be an ES module file
+  let Some(dir) = file_path.parent() else {
+    return Ok(());
+  };
+  ensure_read_permission::<P>(state, dir)?;
+  let node_resolver = state.borrow::<NodeResolverRc>().clone();
+  node_resolver.ensure_requireable(&file_path, maybe_parent_filename.as_deref())
+}
+
+#[op2]
+#[string]
+pub fn op_require_closest_package_json_path<P>(
+  state: &mut OpState,
+  #[string] filename: String,
+) -> Result<Option<String>, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let file_path = PathBuf::from(filename);
+  let Some(dir) = file_path.parent() else {
+    return Ok(None);
+  };
+  ensure_read_permission::<P>(state, dir)?;
+  let node_resolver = state.borrow::<NodeResolverRc>().clone();
+  Ok(
+    node_resolver
+      .get_closest_package_json_path(&file_path)?
+      .map(|path| path.to_string_lossy().to_string()),
+  )
+}
+
+#[op2]
+#[string]
+pub fn op_require_closest_tsconfig<P>(
+  state: &mut OpState,
+  #[string] filename: String,
+) -> Result<Option<String>, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let file_path = PathBuf::from(filename);
+  let Some(dir) = file_path.parent() else {
+    return Ok(None);
+  };
+  ensure_read_permission::<P>(state, dir)?;
+  let node_resolver = state.borrow::<NodeResolverRc>().clone();
+  Ok(
+    node_resolver
+      .get_closest_tsconfig_path(&file_path)?
+      .map(|path| path.to_string_lossy().to_string()),
+  )
+}
+
+/// Returns the directory containing the closest package.json to
+/// `filename` without reading it, which is what config loaders resolve
+/// their files relative to.
+#[op2]
+#[string]
+pub fn op_require_package_root<P>(
+  state: &mut OpState,
+  #[string] filename: String,
+) -> Result<Option<String>, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let file_path = PathBuf::from(filename);
+  let Some(dir) = file_path.parent() else {
+    return Ok(None);
+  };
+  ensure_read_permission::<P>(state, dir)?;
+  let node_resolver = state.borrow::<NodeResolverRc>().clone();
+  package_root(&node_resolver, &file_path)
+}
+
+fn package_root(
+  node_resolver: &resolution::NodeResolver,
+  file_path: &Path,
+) -> Result<Option<String>, AnyError> {
+  Ok(
+    node_resolver
+      .get_closest_package_json_path(file_path)?
+      .and_then(|path| {
+        path.parent().map(|dir| dir.to_string_lossy().to_string())
+      }),
+  )
+}
+
+/// Returns the `version` of the package in `pkg_path`, which may be the
+/// package directory or its package.json.
+#[op2]
+#[string]
+pub fn op_require_package_version<P>(
+  state: &mut OpState,
+  #[string] pkg_path: String,
+) -> Result<Option<String>, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let package_json_path = package_json_path_for(Path::new(&pkg_path));
+  ensure_read_permission::<P>(state, &package_json_path)?;
+  let node_resolver = state.borrow::<NodeResolverRc>().clone();
+  package_version(&node_resolver, &package_json_path)
+}
+
+/// Returns the ESM entry point given by the "module" field of the package
+/// in `pkg_path`, when the resolver was configured to honour it.
+#[op2]
+#[string]
+pub fn op_require_esm_entry<P>(
+  state: &mut OpState,
+  #[string] pkg_path: String,
+) -> Result<Option<String>, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let package_json_path = package_json_path_for(Path::new(&pkg_path));
+  ensure_read_permission::<P>(state, &package_json_path)?;
+  let node_resolver = state.borrow::<NodeResolverRc>().clone();
+  let Some(package_json) =
+    node_resolver.load_package_json(&package_json_path)?
+  else {
+    return Ok(None);
+  };
+  Ok(
+    node_resolver
+      .esm_entry(&package_json)?
+      .map(|url| url_to_file_path_string(&url))
+      .transpose()?,
+  )
+}
+
+fn package_json_path_for(pkg_path: &Path) -> PathBuf {
+  if pkg_path.file_name() == Some("package.json".as_ref()) {
+    pkg_path.to_path_buf()
+  } else {
+    pkg_path.join("package.json")
+  }
+}
+
+fn package_version(
+  node_resolver: &resolution::NodeResolver,
+  package_json_path: &Path,
+) -> Result<Option<String>, AnyError> {
+  Ok(
+    node_resolver
+      .load_package_json(package_json_path)?
+      .and_then(|pkg| pkg.version.clone()),
+  )
+}
+
 #[op2]
 #[serde]
 pub fn op_require_read_package_scope<P>(
@@ -562,12 +1538,76 @@ where
     .map(|pkg| (*pkg).clone())
 }
 
+#[op2]
+#[serde]
+pub fn op_require_package_json_field<P>(
+  state: &mut OpState,
+  #[string] package_json_path: String,
+  #[string] field: String,
+) -> Result<Option<Value>, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let package_json_path = PathBuf::from(package_json_path);
+  if package_json_path.file_name() != Some("package.json".as_ref()) {
+    // permissions: do not allow reading a non-package.json file
+    return Ok(None);
+  }
+  ensure_read_permission::<P>(state, &package_json_path)?;
+  let node_resolver = state.borrow::<NodeResolverRc>();
+  let Some(package_json) =
+    node_resolver.load_package_json_value(&package_json_path)?
+  else {
+    return Ok(None);
+  };
+  Ok(package_json.get(&field).cloned())
+}
+
+#[op2]
+#[string]
+pub fn op_require_resolve_bin<P>(
+  state: &mut OpState,
+  #[string] package_json_path: String,
+  #[string] bin_name: Option<String>,
+) -> Result<Option<String>, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let package_json_path = PathBuf::from(package_json_path);
+  if package_json_path.file_name() != Some("package.json".as_ref()) {
+    // permissions: do not allow reading a non-package.json file
+    return Ok(None);
+  }
+  ensure_read_permission::<P>(state, &package_json_path)?;
+  let node_resolver = state.borrow::<NodeResolverRc>().clone();
+  let Some(package_json) =
+    node_resolver.load_package_json(&package_json_path)?
+  else {
+    return Ok(None);
+  };
+  if package_json.bin.is_none() {
+    return Ok(None);
+  }
+  let bin_entry =
+    resolution::resolve_bin_entry_value(&package_json, bin_name.as_deref())?;
+  Ok(Some(
+    package_json_path
+      .parent()
+      .unwrap()
+      .join(bin_entry)
+      .clean()
+      .to_string_lossy()
+      .to_string(),
+  ))
+}
+
 #[op2]
 #[string]
 pub fn op_require_package_imports_resolve<P>(
   state: &mut OpState,
   #[string] referrer_filename: String,
   #[string] request: String,
+  #[serde] conditions: Option<Vec<String>>,
 ) -> Result<Option<String>, AnyError>
 where
   P: NodePermissions + 'static,
@@ -575,29 +1615,182 @@ where
   let referrer_path = PathBuf::from(&referrer_filename);
   ensure_read_permission::<P>(state, &referrer_path)?;
   let node_resolver = state.borrow::<NodeResolverRc>();
+  package_imports_resolve(
+    node_resolver,
+    &referrer_path,
+    &request,
+    NodeModuleKind::Cjs,
+    conditions.as_deref(),
+  )
+}
+
+/// Like `op_require_package_imports_resolve`, but for an ES module
+/// referrer, so the "import" conditions are used by default.
+#[op2]
+#[string]
+pub fn op_require_package_imports_resolve_esm<P>(
+  state: &mut OpState,
+  #[string] referrer_filename: String,
+  #[string] request: String,
+  #[serde] conditions: Option<Vec<String>>,
+) -> Result<Option<String>, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let referrer_path = PathBuf::from(&referrer_filename);
+  ensure_read_permission::<P>(state, &referrer_path)?;
+  let node_resolver = state.borrow::<NodeResolverRc>();
+  package_imports_resolve(
+    node_resolver,
+    &referrer_path,
+    &request,
+    NodeModuleKind::Esm,
+    conditions.as_deref(),
+  )
+}
+
+fn package_imports_resolve(
+  node_resolver: &resolution::NodeResolver,
+  referrer_path: &Path,
+  request: &str,
+  referrer_kind: NodeModuleKind,
+  conditions: Option<&[String]>,
+) -> Result<Option<String>, AnyError> {
   let Some(pkg) =
-    node_resolver.get_closest_package_json_from_path(&referrer_path)?
+    node_resolver.get_closest_package_json_from_path(referrer_path)?
   else {
     return Ok(None);
   };
 
   if pkg.imports.is_some() {
-    let referrer_url =
-      deno_core::url::Url::from_file_path(&referrer_filename).unwrap();
-    let url = node_resolver.package_imports_resolve(
-      &request,
-      &referrer_url,
-      NodeModuleKind::Cjs,
-      Some(&pkg),
-      resolution::REQUIRE_CONDITIONS,
-      NodeResolutionMode::Execution,
-    )?;
+    let referrer_url = Url::from_file_path(referrer_path).unwrap();
+    let url = match conditions {
+      Some(conditions) => {
+        let conditions =
+          conditions.iter().map(|c| c.as_str()).collect::<Vec<_>>();
+        node_resolver.package_imports_resolve_exact(
+          request,
+          &referrer_url,
+          referrer_kind,
+          Some(&pkg),
+          &conditions,
+          NodeResolutionMode::Execution,
+        )?
+      }
+      None => node_resolver.package_imports_resolve(
+        request,
+        &referrer_url,
+        referrer_kind,
+        Some(&pkg),
+        resolution::conditions_for_kind(referrer_kind),
+        NodeResolutionMode::Execution,
+      )?,
+    };
     Ok(Some(url_to_file_path_string(&url)?))
   } else {
     Ok(None)
   }
 }
 
+/// Resolves a bare or `#` imports specifier under the provided conditions
+/// instead of the default require conditions, which allows tooling to
+/// resolve the same specifier for different environments. Relative and
+/// absolute specifiers aren't affected by conditions and resolve to `None`.
+#[op2]
+#[string]
+pub fn op_require_resolve_with_conditions<P>(
+  state: &mut OpState,
+  #[string] specifier: String,
+  #[string] parent_filename: String,
+  #[serde] conditions: Vec<String>,
+) -> Result<Option<String>, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let parent_path = PathBuf::from(&parent_filename);
+  ensure_read_permission::<P>(state, &parent_path)?;
+  let node_resolver = state.borrow::<NodeResolverRc>();
+  let conditions = conditions.iter().map(|c| c.as_str()).collect::<Vec<_>>();
+  resolve_with_conditions(node_resolver, &specifier, &parent_path, &conditions)?
+    .map(|url| {
+      if url.scheme() == "file" {
+        url_to_file_path_string(&url)
+      } else {
+        Ok(url.to_string())
+      }
+    })
+    .transpose()
+}
+
+fn resolve_with_conditions(
+  node_resolver: &resolution::NodeResolver,
+  specifier: &str,
+  parent_path: &Path,
+  conditions: &[&str],
+) -> Result<Option<Url>, AnyError> {
+  // the caller supplies the complete condition set, so neither the custom
+  // conditions nor `node-addons` are added to it
+  let referrer_kind = if conditions.contains(&"import") {
+    NodeModuleKind::Esm
+  } else {
+    NodeModuleKind::Cjs
+  };
+  let referrer = file_path_to_url(parent_path)?;
+
+  if specifier.starts_with('#') {
+    let pkg = node_resolver.get_closest_package_json_from_path(parent_path)?;
+    Ok(Some(node_resolver.package_imports_resolve_exact(
+      specifier,
+      &referrer,
+      referrer_kind,
+      pkg.as_deref(),
+      conditions,
+      NodeResolutionMode::Execution,
+    )?))
+  } else if resolution::should_be_treated_as_relative_or_absolute_path(
+    specifier,
+  ) || Path::new(specifier).is_absolute()
+  {
+    Ok(None)
+  } else {
+    Ok(node_resolver.package_resolve_exact(
+      specifier,
+      &referrer,
+      referrer_kind,
+      conditions,
+      NodeResolutionMode::Execution,
+    )?)
+  }
+}
+
+/// Resolves the declaration file of a specifier, matching the "types"
+/// condition of "exports" and otherwise probing for a declaration file next
+/// to the resolved module. Built-in modules resolve to `None`.
+#[op2]
+#[string]
+pub fn op_require_resolve_types<P>(
+  state: &mut OpState,
+  #[string] specifier: String,
+  #[string] parent_filename: String,
+) -> Result<Option<String>, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let parent_path = PathBuf::from(&parent_filename);
+  ensure_read_permission::<P>(state, &parent_path)?;
+  let node_resolver = state.borrow::<NodeResolverRc>();
+  let referrer = file_path_to_url(&parent_path)?;
+  let Some(resolution) =
+    node_resolver.resolve(&specifier, &referrer, NodeResolutionMode::Types)?
+  else {
+    return Ok(None);
+  };
+  match resolution {
+    NodeResolution::BuiltIn(_) => Ok(None),
+    resolution => url_to_file_path_string(&resolution.into_url()).map(Some),
+  }
+}
+
 #[op2(fast)]
 pub fn op_require_break_on_next_statement(state: &mut OpState) {
   let inspector = state.borrow::<Rc<RefCell<JsRuntimeInspector>>>();
@@ -611,6 +1804,18 @@ fn url_to_file_path_string(url: &Url) -> Result<String, AnyError> {
   Ok(file_path.to_string_lossy().to_string())
 }
 
+fn file_path_to_url(path: &Path) -> Result<Url, AnyError> {
+  match Url::from_file_path(path) {
+    Ok(url) => Ok(url),
+    Err(()) => {
+      deno_core::anyhow::bail!(
+        "failed to convert '{}' to file URL",
+        path.display()
+      )
+    }
+  }
+}
+
 fn url_to_file_path(url: &Url) -> Result<PathBuf, AnyError> {
   match url.to_file_path() {
     Ok(file_path) => Ok(file_path),
@@ -619,3 +1824,994 @@ fn url_to_file_path(url: &Url) -> Result<PathBuf, AnyError> {
     }
   }
 }
+
+#[cfg(all(test, unix))]
+mod test {
+  use crate::test_util::build_resolver;
+  use crate::test_util::build_resolver_with_fs;
+  use crate::test_util::in_memory_fs;
+  use crate::test_util::TestNpmResolver;
+
+  use super::*;
+
+  #[test]
+  fn node_module_paths_only_matches_whole_segments() {
+    assert_eq!(
+      node_module_paths(
+        Path::new("/app/src/node_modules_helpers/pkg"),
+        &["node_modules"]
+      ),
+      vec![
+        "/app/src/node_modules_helpers/pkg/node_modules",
+        "/app/src/node_modules_helpers/node_modules",
+        "/app/src/node_modules",
+        "/app/node_modules",
+        "/node_modules",
+      ]
+    );
+    assert_eq!(
+      node_module_paths(Path::new("/app/node_modules/pkg"), &["node_modules"]),
+      vec![
+        "/app/node_modules/pkg/node_modules",
+        "/app/node_modules",
+        "/node_modules",
+      ]
+    );
+  }
+
+  #[test]
+  fn node_module_paths_prefers_nested_duplicate() {
+    let fs = in_memory_fs(&[
+      ("/app/node_modules/a/node_modules/dep/index.js", ""),
+      ("/app/node_modules/dep/index.js", ""),
+    ]);
+    let extensions = ResolvableExtensions::default().0;
+    // the first lookup path with a match wins, like Module._findPath
+    let found = node_module_paths(
+      Path::new("/app/node_modules/a/lib"),
+      &["node_modules"],
+    )
+    .into_iter()
+    .find_map(|dir| {
+      probe_module_path(
+        &fs,
+        &Path::new(&dir).join("dep"),
+        &extensions,
+        &mut |_| Ok(()),
+      )
+      .unwrap()
+    });
+    assert_eq!(
+      found,
+      Some(PathBuf::from(
+        "/app/node_modules/a/node_modules/dep/index.js"
+      ))
+    );
+  }
+
+  #[test]
+  fn node_module_paths_from_node_modules_dir() {
+    assert_eq!(
+      node_module_paths(Path::new("/app/node_modules"), &["node_modules"]),
+      vec!["/app/node_modules", "/node_modules"]
+    );
+    assert_eq!(
+      node_module_paths(
+        Path::new("/app/node_modules/node_modules"),
+        &["node_modules"]
+      ),
+      vec!["/app/node_modules", "/node_modules"]
+    );
+    assert_eq!(
+      node_module_paths(Path::new("/node_modules"), &["node_modules"]),
+      vec!["/node_modules"]
+    );
+  }
+
+  #[test]
+  fn node_module_paths_custom_dir_names() {
+    use deno_fs::FileSystem;
+
+    let dir_names = ["node_modules", ".packages"];
+    assert_eq!(
+      node_module_paths(Path::new("/app/.packages/pkg"), &dir_names),
+      vec![
+        "/app/.packages/pkg/node_modules",
+        "/app/.packages/pkg/.packages",
+        "/app/node_modules",
+        "/app/.packages",
+        "/node_modules",
+        "/.packages",
+      ]
+    );
+
+    let fs = in_memory_fs(&[(
+      "/app/.packages/pkg/package.json",
+      r#"{ "name": "pkg" }"#,
+    )]);
+    let package_dir = node_module_paths(Path::new("/app/src"), &dir_names)
+      .into_iter()
+      .map(|dir| PathBuf::from(dir).join("pkg"))
+      .find(|dir| fs.is_file_sync(&dir.join("package.json")));
+    assert_eq!(package_dir, Some(PathBuf::from("/app/.packages/pkg")));
+  }
+
+  #[test]
+  fn strip_extended_length_prefix_test() {
+    let strip = |path: &str| strip_extended_length_prefix(path.to_string());
+    assert_eq!(strip(r"\\?\C:\a\b.js"), r"C:\a\b.js");
+    assert_eq!(
+      strip(r"\\?\UNC\server\share\a\b.js"),
+      r"\\server\share\a\b.js"
+    );
+    assert_eq!(strip(r"\\?\UNC\server\share"), r"\\server\share");
+    // volume paths can't be expressed without the prefix
+    assert_eq!(strip(r"\\?\Volume{1234}\a.js"), r"\\?\Volume{1234}\a.js");
+    assert_eq!(strip(r"\\server\share\a.js"), r"\\server\share\a.js");
+    assert_eq!(strip("/app/a.js"), "/app/a.js");
+  }
+
+  #[test]
+  fn path_resolve_matches_node() {
+    let cases: &[(&[&str], &str)] = &[
+      (&["/foo/bar", "./baz"], "/foo/bar/baz"),
+      (&["/foo/bar", "/tmp/file/"], "/tmp/file"),
+      (&["/a", ".", "b"], "/a/b"),
+      (&["/a", "", "b"], "/a/b"),
+      (&["/a", "b", ""], "/a/b"),
+      (&["", "/a"], "/a"),
+      (&["/a/b", ".."], "/a"),
+      (&["/a/b", "../..", ".."], "/"),
+      (&["/", ".."], "/"),
+      (&["/a//b"], "/a/b"),
+      (&["/a/./b/."], "/a/b"),
+      (&["/a", "/b", "c"], "/b/c"),
+      (&["/a/", "b/"], "/a/b"),
+      (&["/a", "./b/../c"], "/a/c"),
+      (&["/a", "..", "b", ".", "c"], "/b/c"),
+      (&["/"], "/"),
+      (&["/a", "b", "/"], "/"),
+      (&["a", "b"], "/cwd/a/b"),
+      (&["a", "..", ".."], "/"),
+      (&[""], "/cwd"),
+    ];
+    for (parts, expected) in cases {
+      let parts = parts.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+      let actual = path_resolve(&parts, || Ok(PathBuf::from("/cwd"))).unwrap();
+      assert_eq!(actual, *expected, "{:?}", parts);
+    }
+  }
+
+  #[test]
+  fn is_typescript_file_by_extension() {
+    for path in ["/a.ts", "/a.tsx", "/a.mts", "/a.cts", "/a.d.ts"] {
+      assert!(is_typescript_file(Path::new(path)), "{path}");
+    }
+    for path in ["/a.js", "/a.mjs", "/a.json", "/a.ts.js", "/ts", "/a.TS"] {
+      assert!(!is_typescript_file(Path::new(path)), "{path}");
+    }
+  }
+
+  #[test]
+  fn is_request_bare_test() {
+    for request in ["pkg", "@scope/pkg/sub", "pkg/./a", ".pkg"] {
+      assert!(is_request_bare(request), "{request}");
+    }
+    for request in ["", ".", "..", "./a", "../a", "/a", "node:fs"] {
+      assert!(!is_request_bare(request), "{request}");
+    }
+  }
+
+  #[test]
+  fn validate_specifier_test() {
+    for request in ["pkg", "./a.js", "/a.js", "node:fs", "file:///a.js"] {
+      assert!(validate_specifier(request).is_ok(), "{request}");
+    }
+    assert_eq!(
+      validate_specifier("").unwrap_err().to_string(),
+      "[ERR_INVALID_MODULE_SPECIFIER] Invalid module \"\" must be a non-empty string"
+    );
+    assert_eq!(
+      validate_specifier("https://example.com/a.js")
+        .unwrap_err()
+        .to_string(),
+      "[ERR_INVALID_MODULE_SPECIFIER] Invalid module \"https://example.com/a.js\" has an unsupported URL scheme"
+    );
+    assert!(validate_specifier("./a\0.js").is_err());
+  }
+
+  #[test]
+  fn windows_reserved_names() {
+    for request in ["con", "./NUL.js", "pkg/aux/index.js", "lpt1.txt", "COM9"] {
+      assert!(has_windows_reserved_name(request), "{request}");
+    }
+    for request in ["console", "./null.js", "com10", "lpt", "pkg/com0.js"] {
+      assert!(!has_windows_reserved_name(request), "{request}");
+    }
+  }
+
+  #[test]
+  fn stat_kind_codes() {
+    let fs = in_memory_fs(&[("/app/a.js", "")]);
+    assert_eq!(stat_kind(&fs, Path::new("/app/a.js")), 0);
+    assert_eq!(stat_kind(&fs, Path::new("/app")), 1);
+    assert_eq!(stat_kind(&fs, Path::new("/app/missing.js")), -1);
+  }
+
+  #[test]
+  fn probe_module_path_extensions_and_index() {
+    let fs = in_memory_fs(&[
+      ("/app/a.js", ""),
+      ("/app/b.json", ""),
+      ("/app/b.js", ""),
+      ("/app/dir/index.json", ""),
+    ]);
+    let extensions = vec![".js".to_string(), ".json".to_string()];
+    let probe = |request: &str| {
+      probe_module_path(&fs, Path::new(request), &extensions, &mut |_| Ok(()))
+        .unwrap()
+        .map(|p| p.to_string_lossy().to_string())
+    };
+    assert_eq!(probe("/app/a.js").as_deref(), Some("/app/a.js"));
+    assert_eq!(probe("/app/a").as_deref(), Some("/app/a.js"));
+    // extension order is respected
+    assert_eq!(probe("/app/b").as_deref(), Some("/app/b.js"));
+    assert_eq!(probe("/app/dir").as_deref(), Some("/app/dir/index.json"));
+    assert_eq!(probe("/app/missing"), None);
+  }
+
+  #[test]
+  fn probe_module_path_checks_every_candidate() {
+    let fs = in_memory_fs(&[("/app/dir/index.json", ""), ("/secret/a.js", "")]);
+    let extensions = vec![".js".to_string(), ".json".to_string()];
+
+    let mut checked = Vec::new();
+    let found =
+      probe_module_path(&fs, Path::new("/app/dir"), &extensions, &mut |p| {
+        checked.push(p.to_string_lossy().to_string());
+        Ok(())
+      })
+      .unwrap();
+    assert_eq!(found, Some(PathBuf::from("/app/dir/index.json")));
+    assert_eq!(
+      checked,
+      vec![
+        "/app/dir",
+        "/app/dir.js",
+        "/app/dir.json",
+        "/app/dir/index.js",
+        "/app/dir/index.json",
+      ]
+    );
+
+    // a denied candidate fails the probe instead of being stat-ed
+    let err =
+      probe_module_path(&fs, Path::new("/secret/a"), &extensions, &mut |p| {
+        if p.starts_with("/secret") && p != Path::new("/secret/a") {
+          Err(generic_error(format!("denied '{}'", p.display())))
+        } else {
+          Ok(())
+        }
+      })
+      .unwrap_err();
+    assert_eq!(err.to_string(), "denied '/secret/a.js'");
+  }
+
+  #[test]
+  fn probe_module_path_configured_extensions() {
+    let fs = in_memory_fs(&[("/app/foo.ts", ""), ("/app/dir/index.ts", "")]);
+    let default_extensions = ResolvableExtensions::default().0;
+    assert_eq!(default_extensions, vec![".js", ".json", ".node"]);
+    assert_eq!(
+      probe_module_path(
+        &fs,
+        Path::new("/app/foo"),
+        &default_extensions,
+        &mut |_| Ok(())
+      )
+      .unwrap(),
+      None
+    );
+
+    let mut extensions = default_extensions;
+    extensions.push(".ts".to_string());
+    assert_eq!(
+      probe_module_path(&fs, Path::new("/app/foo"), &extensions, &mut |_| Ok(
+        ()
+      ))
+      .unwrap(),
+      Some(PathBuf::from("/app/foo.ts"))
+    );
+    assert_eq!(
+      probe_module_path(&fs, Path::new("/app/dir"), &extensions, &mut |_| Ok(
+        ()
+      ))
+      .unwrap(),
+      Some(PathBuf::from("/app/dir/index.ts"))
+    );
+  }
+
+  #[test]
+  fn parse_node_options_preloads_test() {
+    assert_eq!(parse_node_options_preloads(""), Vec::<String>::new());
+    assert_eq!(
+      parse_node_options_preloads(
+        "--require ./a.js -r b --max-old-space-size=100 --require=c"
+      ),
+      vec!["./a.js", "b", "c"]
+    );
+    assert_eq!(
+      parse_node_options_preloads(
+        r#"-r "./dir with spaces/a.js" --require "./\"quoted\".js""#
+      ),
+      vec!["./dir with spaces/a.js", "./\"quoted\".js"]
+    );
+    // missing value
+    assert_eq!(
+      parse_node_options_preloads("--inspect -r"),
+      Vec::<String>::new()
+    );
+  }
+
+  #[test]
+  fn module_dirname_follows_symlinks() {
+    use deno_fs::FileSystem;
+    use deno_fs::RealFs;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let dir = RealFs.realpath_sync(temp_dir.path()).unwrap();
+    let real_dir = dir.join("node_modules/.deno/pkg@1.0.0/node_modules/pkg");
+    RealFs.mkdir_sync(&real_dir, true, 0o755).unwrap();
+    RealFs
+      .write_file_sync(
+        &real_dir.join("index.js"),
+        deno_fs::OpenOptions::write(true, false, false, None),
+        None,
+        b"",
+      )
+      .unwrap();
+    RealFs
+      .symlink_sync(&real_dir, &dir.join("node_modules/pkg"), None)
+      .unwrap();
+
+    let dirname =
+      module_dirname(&RealFs, &dir.join("node_modules/pkg/index.js")).unwrap();
+    assert_eq!(dirname, real_dir.to_string_lossy());
+    assert!(module_dirname(&RealFs, &dir.join("missing.js")).is_err());
+  }
+
+  #[test]
+  fn try_self_types_condition() {
+    let node_resolver = build_resolver(&[
+      (
+        "/app/package.json",
+        r#"{ "name": "@scope/pkg", "exports": { ".": { "types": "./index.d.ts", "default": "./index.js" } } }"#,
+      ),
+      ("/app/index.d.ts", ""),
+      ("/app/index.js", ""),
+      ("/app/src/main.ts", ""),
+    ]);
+    let root = PathBuf::from("/app");
+    let parent = root.join("src/main.ts");
+    let resolve = |mode| {
+      try_self(&node_resolver, parent.to_str().unwrap(), "@scope/pkg", mode)
+        .unwrap()
+    };
+    assert_eq!(
+      resolve(NodeResolutionMode::Types),
+      Some(root.join("index.d.ts").to_string_lossy().to_string())
+    );
+    assert_eq!(
+      resolve(NodeResolutionMode::Execution),
+      Some(root.join("index.js").to_string_lossy().to_string())
+    );
+  }
+
+  #[test]
+  fn resolve_package_dir_exports_absolute() {
+    let node_resolver = build_resolver(&[
+      (
+        "/app/node_modules/pkg/package.json",
+        r#"{ "main": "./main.js", "exports": { ".": "./dist/index.js" } }"#,
+      ),
+      ("/app/node_modules/pkg/main.js", ""),
+      ("/app/node_modules/pkg/index.js", ""),
+      ("/app/node_modules/pkg/dist/index.js", ""),
+      (
+        "/app/lib/package.json",
+        r#"{ "exports": "./dist/index.js" }"#,
+      ),
+      (
+        "/app/node_modules/subpaths-only/package.json",
+        r#"{ "main": "./main.js", "exports": { "./sub": "./sub.js" } }"#,
+      ),
+      (
+        "/app/node_modules/pkg/nested/package.json",
+        r#"{ "exports": "./dist/index.js" }"#,
+      ),
+      (
+        "/app/node_modules/@scope/pkg/package.json",
+        r#"{ "exports": "./dist/index.js" }"#,
+      ),
+    ]);
+    let dir_names = ModulesDirNames::default().0;
+    let referrer = Url::parse("file:///app/main.js").unwrap();
+    let resolve = |pkg_dir: &str| {
+      resolve_package_dir_exports(
+        &node_resolver,
+        Path::new(pkg_dir),
+        &dir_names,
+        &referrer,
+      )
+      .unwrap()
+    };
+    // exports wins over "main" and index.js
+    assert_eq!(
+      resolve("/app/node_modules/pkg"),
+      Some(PathBuf::from("/app/node_modules/pkg/dist/index.js"))
+    );
+    assert_eq!(
+      resolve("/app/node_modules/@scope/pkg"),
+      Some(PathBuf::from("/app/node_modules/@scope/pkg/dist/index.js"))
+    );
+    // not a dependency, so plain directory resolution applies
+    assert_eq!(resolve("/app/lib"), None);
+    // a directory inside of a package isn't the package itself
+    assert_eq!(resolve("/app/node_modules/pkg/nested"), None);
+    // "." isn't exported, so "main" is still used
+    assert_eq!(resolve("/app/node_modules/subpaths-only"), None);
+  }
+
+  #[test]
+  fn file_path_to_url_rejects_relative_paths() {
+    assert_eq!(
+      file_path_to_url(Path::new("/app/main.js"))
+        .unwrap()
+        .as_str(),
+      "file:///app/main.js"
+    );
+    let err = file_path_to_url(Path::new("main.js")).unwrap_err();
+    assert_eq!(err.to_string(), "failed to convert 'main.js' to file URL");
+  }
+
+  #[test]
+  fn package_root_skips_parsing() {
+    let node_resolver = build_resolver(&[
+      // not valid JSON, which is fine because it's never read
+      ("/app/pkg/package.json", "{"),
+      ("/app/pkg/src/lib/index.js", ""),
+      ("/app/other/index.js", ""),
+    ]);
+    assert_eq!(
+      package_root(&node_resolver, Path::new("/app/pkg/src/lib/index.js"))
+        .unwrap(),
+      Some("/app/pkg".to_string())
+    );
+    // outside of any package
+    assert_eq!(
+      package_root(&node_resolver, Path::new("/app/other/index.js")).unwrap(),
+      None
+    );
+    // neither has a directory to start from
+    assert_eq!(package_root(&node_resolver, Path::new("")).unwrap(), None);
+    assert_eq!(package_root(&node_resolver, Path::new("/")).unwrap(), None);
+  }
+
+  #[test]
+  fn closest_tsconfig_walks_parents() {
+    let node_resolver = build_resolver(&[
+      ("/project/tsconfig.json", "{}"),
+      ("/project/src/lib/mod.ts", ""),
+      ("/project/packages/a/tsconfig.json", "{}"),
+      ("/project/packages/a/index.ts", ""),
+    ]);
+    let root = PathBuf::from("/");
+    assert_eq!(
+      node_resolver
+        .get_closest_tsconfig_path(&root.join("project/src/lib/mod.ts"))
+        .unwrap(),
+      Some(root.join("project/tsconfig.json"))
+    );
+    // the closest project wins in a monorepo
+    assert_eq!(
+      node_resolver
+        .get_closest_tsconfig_path(&root.join("project/packages/a/index.ts"))
+        .unwrap(),
+      Some(root.join("project/packages/a/tsconfig.json"))
+    );
+  }
+
+  #[test]
+  fn package_version_from_dir_or_package_json() {
+    let node_resolver = build_resolver(&[
+      (
+        "/app/node_modules/pkg/package.json",
+        r#"{ "name": "pkg", "version": "1.2.3" }"#,
+      ),
+      (
+        "/app/node_modules/unversioned/package.json",
+        r#"{ "name": "unversioned" }"#,
+      ),
+    ]);
+    let root = PathBuf::from("/app");
+    let version = |pkg_path: PathBuf| {
+      package_version(&node_resolver, &package_json_path_for(&pkg_path))
+        .unwrap()
+    };
+    assert_eq!(
+      version(root.join("node_modules/pkg")),
+      Some("1.2.3".to_string())
+    );
+    assert_eq!(
+      version(root.join("node_modules/pkg/package.json")),
+      Some("1.2.3".to_string())
+    );
+    assert_eq!(version(root.join("node_modules/unversioned")), None);
+    assert_eq!(version(root.join("node_modules/missing")), None);
+  }
+
+  #[test]
+  fn package_imports_resolve_by_referrer_kind() {
+    let node_resolver = build_resolver(&[
+      (
+        "/app/package.json",
+        r##"{ "imports": { "#internal": { "import": "./esm.mjs", "require": "./cjs.cjs" } } }"##,
+      ),
+      ("/app/esm.mjs", ""),
+      ("/app/cjs.cjs", ""),
+    ]);
+    let root = PathBuf::from("/app");
+    let referrer = root.join("main.js");
+    let resolve = |kind| {
+      package_imports_resolve(
+        &node_resolver,
+        &referrer,
+        "#internal",
+        kind,
+        None,
+      )
+      .unwrap()
+    };
+    assert_eq!(
+      resolve(NodeModuleKind::Esm),
+      Some(root.join("esm.mjs").to_string_lossy().to_string())
+    );
+    assert_eq!(
+      resolve(NodeModuleKind::Cjs),
+      Some(root.join("cjs.cjs").to_string_lossy().to_string())
+    );
+  }
+
+  #[test]
+  fn resolve_with_conditions_ignores_implicit_conditions() {
+    let node_resolver = build_resolver(&[
+      (
+        "/app/node_modules/dep/package.json",
+        r#"{ "exports": { "custom": "./custom.js", "require": "./cjs.js" } }"#,
+      ),
+      ("/app/node_modules/dep/custom.js", ""),
+      ("/app/node_modules/dep/cjs.js", ""),
+    ])
+    .with_custom_conditions(vec!["custom".to_string()])
+    .unwrap();
+    let parent = Path::new("/app/main.js");
+    let url =
+      resolve_with_conditions(&node_resolver, "dep", parent, &["require"])
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+      url.to_file_path().unwrap(),
+      Path::new("/app/node_modules/dep/cjs.js")
+    );
+    assert!(resolve_with_conditions(
+      &node_resolver,
+      "dep",
+      Path::new("main.js"),
+      &["require"],
+    )
+    .is_err());
+  }
+
+  #[test]
+  fn create_require_context_resolves_from_filename() {
+    use deno_fs::FileSystem;
+
+    let fs = in_memory_fs(&[
+      (
+        "/app/package.json",
+        r#"{ "name": "app", "type": "module" }"#,
+      ),
+      ("/app/src/util.js", ""),
+      (
+        "/app/node_modules/dep/package.json",
+        r#"{ "exports": "./main.js" }"#,
+      ),
+      ("/app/node_modules/dep/main.js", ""),
+    ]);
+    let fs: FileSystemRc = deno_fs::sync::MaybeArc::new(fs);
+    let root = PathBuf::from("/");
+    let node_resolver = build_resolver_with_fs(fs.clone());
+    // the file passed to createRequire doesn't need to exist
+    let context = create_require_context(
+      &node_resolver,
+      &root.join("app/src/../src/config.cjs"),
+      &ModulesDirNames::default().0,
+    );
+    assert_eq!(
+      context.filename,
+      root.join("app/src/config.cjs").to_string_lossy()
+    );
+    assert_eq!(
+      context.package_json_path,
+      Some(root.join("app/package.json").to_string_lossy().to_string())
+    );
+    assert_eq!(context.package_type.as_deref(), Some("module"));
+
+    // a relative specifier resolves from the directory of the file
+    let relative = Path::new(&context.dirname).join("util.js");
+    assert!(fs.is_file_sync(&relative));
+
+    // a bare specifier resolves from the lookup paths
+    let dep_dir = context
+      .paths
+      .iter()
+      .map(|path| Path::new(path).join("dep"))
+      .find(|dir| fs.is_dir_sync(dir))
+      .unwrap();
+    let referrer = Url::from_file_path(&context.filename).unwrap();
+    let resolved = resolve_package_dir_exports(
+      &node_resolver,
+      &dep_dir,
+      &["node_modules".to_string()],
+      &referrer,
+    )
+    .unwrap();
+    assert_eq!(resolved, Some(root.join("app/node_modules/dep/main.js")));
+  }
+
+  #[test]
+  fn create_require_context_for_root() {
+    use deno_fs::RealFs;
+
+    let temp_dir = std::env::temp_dir();
+    let root = temp_dir.ancestors().last().unwrap();
+    let node_resolver =
+      build_resolver_with_fs(deno_fs::sync::MaybeArc::new(RealFs));
+    for filename in [root.to_path_buf(), root.join("tmp").join("..")] {
+      let context = create_require_context(
+        &node_resolver,
+        &filename,
+        &ModulesDirNames::default().0,
+      );
+      assert_eq!(context.filename, root.to_string_lossy());
+      assert_eq!(context.dirname, root.to_string_lossy());
+    }
+  }
+
+  #[test]
+  fn real_path_not_found() {
+    let fs = in_memory_fs(&[("/app/a.js", "")]);
+    let path = Path::new("/app/deleted.js");
+    let err = real_path(&fs, path).unwrap_err();
+    assert_eq!(
+      deno_core::error::get_custom_error_class(&err),
+      Some("NotFound")
+    );
+    assert_eq!(
+      err.to_string(),
+      format!("[MODULE_NOT_FOUND] Cannot find module '{}'", path.display())
+    );
+  }
+
+  #[test]
+  fn real_path_if_exists_returns_none_when_missing() {
+    use deno_fs::FileSystem;
+    use deno_fs::RealFs;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let dir = RealFs.realpath_sync(temp_dir.path()).unwrap();
+    let file = dir.join("mod.js");
+    RealFs
+      .write_file_sync(
+        &file,
+        deno_fs::OpenOptions::write(true, false, false, None),
+        None,
+        b"",
+      )
+      .unwrap();
+    let link = dir.join("link.js");
+    RealFs.symlink_sync(&file, &link, None).unwrap();
+    assert_eq!(real_path_if_exists(&RealFs, &link).unwrap(), Some(file));
+    assert_eq!(
+      real_path_if_exists(&RealFs, &dir.join("missing.js")).unwrap(),
+      None
+    );
+    // a dangling symlink doesn't exist either
+    let dangling = dir.join("dangling.js");
+    RealFs
+      .symlink_sync(&dir.join("missing.js"), &dangling, None)
+      .unwrap();
+    assert_eq!(real_path_if_exists(&RealFs, &dangling).unwrap(), None);
+  }
+
+  #[test]
+  fn cache_key_follows_symlinks() {
+    use deno_fs::FileSystem;
+    use deno_fs::RealFs;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let dir = RealFs.realpath_sync(temp_dir.path()).unwrap();
+    let file = dir.join("mod.js");
+    RealFs
+      .write_file_sync(
+        &file,
+        deno_fs::OpenOptions::write(true, false, false, None),
+        None,
+        b"",
+      )
+      .unwrap();
+    let link = dir.join("link.js");
+    RealFs.symlink_sync(&file, &link, None).unwrap();
+    assert_eq!(
+      cache_key(&RealFs, &link).unwrap(),
+      cache_key(&RealFs, &file).unwrap()
+    );
+  }
+
+  #[test]
+  fn package_json_for_nested_node_modules() {
+    let npm_resolver = TestNpmResolver::new(deno_fs::sync::MaybeArc::new(
+      deno_fs::InMemoryFs::default(),
+    ));
+    let package_json_for = |path: &str| {
+      npm_resolver
+        .package_json_for(Path::new(path))
+        .unwrap()
+        .map(|path| path.to_string_lossy().to_string())
+    };
+    assert_eq!(
+      package_json_for("/app/node_modules/a/lib/index.js").as_deref(),
+      Some("/app/node_modules/a/package.json")
+    );
+    assert_eq!(
+      package_json_for("/app/node_modules/a/node_modules/@scope/b/index.js")
+        .as_deref(),
+      Some("/app/node_modules/a/node_modules/@scope/b/package.json")
+    );
+    assert_eq!(package_json_for("/app/src/index.js"), None);
+    assert_eq!(package_json_for("/app/node_modules/@scope"), None);
+  }
+
+  #[test]
+  fn self_reference_expansion_scoped() {
+    let cases = [
+      ("pkg", "pkg", Some(".")),
+      ("pkg", "pkg/feature", Some("./feature")),
+      ("pkg", "pkgx", None),
+      ("@scope/pkg", "@scope/pkg", Some(".")),
+      ("@scope/pkg", "@scope/pkg/feature", Some("./feature")),
+      ("@scope/pkg", "@scope/pkg/a/b.js", Some("./a/b.js")),
+      ("@scope/pkg", "@scope/pkg-other/feature", None),
+      ("@scope/pkg", "@scope", None),
+      ("@scope/pkg", "pkg/feature", None),
+      ("lodash.merge", "lodash.merge/fp", Some("./fp")),
+      ("lodash.merge", "lodash", None),
+      ("@org/my.pkg", "@org/my.pkg/sub", Some("./sub")),
+      ("@org/my.pkg", "@org/my.pkgsub", None),
+    ];
+    for (pkg_name, request, expected) in cases {
+      assert_eq!(
+        self_reference_expansion(pkg_name, request).as_deref(),
+        expected,
+        "{request} from {pkg_name}"
+      );
+    }
+  }
+
+  #[test]
+  fn ensure_target_read_permission_eacces() {
+    #[derive(Debug)]
+    struct CheckingNpmResolver;
+
+    impl NpmResolver for CheckingNpmResolver {
+      fn resolve_package_folder_from_package(
+        &self,
+        specifier: &str,
+        _referrer: &ModuleSpecifier,
+      ) -> Result<PathBuf, AnyError> {
+        deno_core::anyhow::bail!("could not find package '{}'", specifier)
+      }
+
+      fn in_npm_package(&self, _specifier: &ModuleSpecifier) -> bool {
+        false
+      }
+
+      fn ensure_read_permission(
+        &self,
+        permissions: &mut dyn NodePermissions,
+        path: &Path,
+      ) -> Result<(), AnyError> {
+        permissions.check_read(path)
+      }
+    }
+
+    struct DenyDirPermissions(PathBuf);
+
+    impl NodePermissions for DenyDirPermissions {
+      fn check_net_url(
+        &mut self,
+        _url: &Url,
+        _api_name: &str,
+      ) -> Result<(), AnyError> {
+        Ok(())
+      }
+      fn check_read_with_api_name(
+        &mut self,
+        path: &Path,
+        _api_name: Option<&str>,
+      ) -> Result<(), AnyError> {
+        if path.starts_with(&self.0) {
+          Err(deno_core::error::custom_error(
+            "PermissionDenied",
+            format!("Requires read access to \"{}\"", path.display()),
+          ))
+        } else {
+          Ok(())
+        }
+      }
+      fn check_write_with_api_name(
+        &mut self,
+        _path: &Path,
+        _api_name: Option<&str>,
+      ) -> Result<(), AnyError> {
+        Ok(())
+      }
+      fn check_sys(
+        &mut self,
+        _kind: &str,
+        _api_name: &str,
+      ) -> Result<(), AnyError> {
+        Ok(())
+      }
+    }
+
+    let mut permissions =
+      DenyDirPermissions(PathBuf::from("/app/node_modules/pkg/dist"));
+    ensure_target_read_permission(
+      &CheckingNpmResolver,
+      &mut permissions,
+      Path::new("/app/node_modules/pkg/index.js"),
+    )
+    .unwrap();
+    let err = ensure_target_read_permission(
+      &CheckingNpmResolver,
+      &mut permissions,
+      Path::new("/app/node_modules/pkg/dist/index.js"),
+    )
+    .unwrap_err();
+    assert_eq!(
+      deno_core::error::get_custom_error_class(&err),
+      Some("PermissionDenied")
+    );
+    assert!(
+      err
+        .to_string()
+        .starts_with("[EACCES] Permission denied, open '/app/node_modules/pkg/dist/index.js'"),
+      "{}",
+      err
+    );
+  }
+
+  /// Reports `exports.<name> =` assignments and `module.exports =
+  /// require("<specifier>")` reexports line by line, and files starting
+  /// with `export` as ES modules.
+  struct LineCjsCodeAnalyzer;
+
+  #[async_trait::async_trait(?Send)]
+  impl CjsCodeAnalyzer for LineCjsCodeAnalyzer {
+    async fn analyze_cjs(
+      &self,
+      _specifier: &ModuleSpecifier,
+      maybe_source: Option<String>,
+    ) -> Result<CjsAnalysis, AnyError> {
+      let source = maybe_source.unwrap();
+      if source.starts_with("export ") {
+        return Ok(CjsAnalysis::Esm(source));
+      }
+      let mut exports = Vec::new();
+      let mut reexports = Vec::new();
+      for line in source.lines() {
+        if let Some(rest) = line.strip_prefix("exports.") {
+          if let Some((name, _)) = rest.split_once(" =") {
+            exports.push(name.to_string());
+          }
+        } else if let Some(rest) =
+          line.strip_prefix("module.exports = require(\"")
+        {
+          if let Some((specifier, _)) = rest.split_once('"') {
+            reexports.push(specifier.to_string());
+          }
+        }
+      }
+      Ok(CjsAnalysis::Cjs(CjsAnalysisExports { exports, reexports }))
+    }
+  }
+
+  #[tokio::test]
+  async fn cjs_named_exports_reports_exports_and_reexports() {
+    let fs = in_memory_fs(&[
+      (
+        "/app/lib.js",
+        "exports.foo = 1;\nexports.bar = function () {};\n",
+      ),
+      ("/app/index.js", "module.exports = require(\"./lib\");\n"),
+      ("/app/esm.js", "export const foo = 1;\n"),
+    ]);
+    let analyzer = LineCjsCodeAnalyzer;
+
+    let lib = cjs_named_exports(&analyzer, &fs, Path::new("/app/lib.js"))
+      .await
+      .unwrap();
+    assert_eq!(lib.exports, vec!["foo", "bar"]);
+    assert!(lib.reexports.is_empty());
+
+    let index = cjs_named_exports(&analyzer, &fs, Path::new("/app/index.js"))
+      .await
+      .unwrap();
+    assert!(index.exports.is_empty());
+    assert_eq!(index.reexports, vec!["./lib"]);
+
+    // ES modules have no CommonJS exports
+    let esm = cjs_named_exports(&analyzer, &fs, Path::new("/app/esm.js"))
+      .await
+      .unwrap();
+    assert!(esm.exports.is_empty());
+    assert!(esm.reexports.is_empty());
+
+    assert!(
+      cjs_named_exports(&analyzer, &fs, Path::new("/app/missing.js"))
+        .await
+        .is_err()
+    );
+  }
+}
+
+#[cfg(all(test, windows))]
+mod windows_test {
+  use super::*;
+
+  #[test]
+  fn is_request_bare_windows_paths() {
+    for request in [r"C:\foo", "C:/foo", r"\\server\share", r"\foo", r".\a"] {
+      assert!(!is_request_bare(request), "{request}");
+    }
+    assert!(is_request_bare("pkg"));
+  }
+}
+
+#[cfg(all(test, any(target_os = "macos", windows)))]
+mod case_insensitive_test {
+  use super::*;
+
+  #[test]
+  fn cache_key_case_insensitive() {
+    use deno_fs::FileSystem;
+    use deno_fs::RealFs;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let dir = RealFs.realpath_sync(temp_dir.path()).unwrap();
+    let file = dir.join("Mod.js");
+    RealFs
+      .write_file_sync(
+        &file,
+        deno_fs::OpenOptions::write(true, false, false, None),
+        None,
+        b"",
+      )
+      .unwrap();
+    assert_eq!(
+      cache_key(&RealFs, &dir.join("mod.JS")).unwrap(),
+      cache_key(&RealFs, &file).unwrap()
+    );
+  }
+}
@@ -1,6 +1,18 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+use std::path::PathBuf;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use deno_core::error::AnyError;
 use deno_core::op2;
 use deno_core::v8;
+use deno_core::OpState;
+use deno_core::ToJsBuffer;
+use deno_fs::FileSystemRc;
+use deno_fs::OpenOptions;
+use serde::Serialize;
+
+use crate::NodePermissions;
 
 #[op2(fast)]
 pub fn op_v8_cached_data_version_tag() -> u32 {
@@ -30,3 +42,102 @@ pub fn op_v8_get_heap_statistics(
   buffer[12] = stats.used_global_handles_size() as f64;
   buffer[13] = stats.external_memory() as f64;
 }
+
+#[derive(Serialize)]
+pub struct HeapSpace {
+  space_name: String,
+  space_size: usize,
+  space_used_size: usize,
+  space_available_size: usize,
+  physical_space_size: usize,
+}
+
+#[op2]
+#[serde]
+pub fn op_v8_get_heap_space_statistics(
+  scope: &mut v8::HandleScope,
+) -> Vec<HeapSpace> {
+  (0..scope.number_of_heap_spaces())
+    .filter_map(|index| scope.get_heap_space_statistics(index))
+    .map(|stats| HeapSpace {
+      space_name: stats.space_name().to_string_lossy().into_owned(),
+      space_size: stats.space_size(),
+      space_used_size: stats.space_used_size(),
+      space_available_size: stats.space_available_size(),
+      physical_space_size: stats.physical_space_size(),
+    })
+    .collect()
+}
+
+#[op2]
+pub fn op_v8_get_heap_code_statistics(
+  scope: &mut v8::HandleScope,
+  #[buffer] buffer: &mut [f64],
+) {
+  if let Some(stats) = scope.get_heap_code_and_metadata_statistics() {
+    buffer[0] = stats.code_and_metadata_size() as f64;
+    buffer[1] = stats.bytecode_and_metadata_size() as f64;
+    buffer[2] = stats.external_script_source_size() as f64;
+    buffer[3] = stats.cpu_profiler_metadata_size() as f64;
+  }
+}
+
+fn take_heap_snapshot(scope: &mut v8::HandleScope) -> Vec<u8> {
+  let mut snapshot = Vec::new();
+  scope.take_heap_snapshot(|chunk| {
+    snapshot.extend_from_slice(chunk);
+    true
+  });
+  snapshot
+}
+
+/// Node names snapshots written without a filename after the time, the
+/// process, the thread and a sequence number, so repeated calls don't
+/// overwrite each other.
+fn default_heap_snapshot_filename(thread_id: u32) -> PathBuf {
+  static SEQUENCE: AtomicUsize = AtomicUsize::new(0);
+  let sequence = SEQUENCE.fetch_add(1, Ordering::Relaxed) + 1;
+  let now = chrono::Utc::now();
+  PathBuf::from(format!(
+    "Heap.{}.{}.{}.{}.{:03}.heapsnapshot",
+    now.format("%Y%m%d"),
+    now.format("%H%M%S"),
+    std::process::id(),
+    thread_id,
+    sequence
+  ))
+}
+
+#[op2]
+#[string]
+pub fn op_v8_write_heap_snapshot<P>(
+  scope: &mut v8::HandleScope,
+  state: &mut OpState,
+  #[string] filename: Option<String>,
+  thread_id: u32,
+) -> Result<String, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let path = filename
+    .map(PathBuf::from)
+    .unwrap_or_else(|| default_heap_snapshot_filename(thread_id));
+  state
+    .borrow_mut::<P>()
+    .check_write_with_api_name(&path, Some("node:v8.writeHeapSnapshot"))?;
+  let snapshot = take_heap_snapshot(scope);
+  let fs = state.borrow::<FileSystemRc>();
+  fs.write_file_sync(
+    &path,
+    OpenOptions::write(true, false, false, None),
+    None,
+    &snapshot,
+  )?;
+  Ok(path.to_string_lossy().to_string())
+}
+
+#[op2]
+#[serde]
+pub fn op_v8_get_heap_snapshot(scope: &mut v8::HandleScope) -> ToJsBuffer {
+  take_heap_snapshot(scope).into()
+}
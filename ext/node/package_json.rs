@@ -3,16 +3,27 @@
 use deno_config::package_json::PackageJson;
 use deno_config::package_json::PackageJsonLoadError;
 use deno_config::package_json::PackageJsonRc;
-use deno_fs::DenoConfigFsAdapter;
+use deno_core::serde_json;
+use deno_core::serde_json::Value;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::io::ErrorKind;
 use std::path::Path;
 use std::path::PathBuf;
+use std::rc::Rc;
 
 // use a thread local cache so that workers have their own distinct cache
 thread_local! {
   static CACHE: RefCell<HashMap<PathBuf, PackageJsonRc>> = RefCell::new(HashMap::new());
+  static RAW_CACHE: RefCell<HashMap<PathBuf, Rc<Value>>> = RefCell::new(HashMap::new());
+  // tolerant loads are cached separately so they never leak into strict ones
+  static TOLERANT_CACHE: RefCell<HashMap<PathBuf, PackageJsonRc>> = RefCell::new(HashMap::new());
+  static TOLERANT_RAW_CACHE: RefCell<HashMap<PathBuf, Rc<Value>>> = RefCell::new(HashMap::new());
+  // package.jsons requested through a symlinked path, keyed by that path
+  static LINK_CACHE: RefCell<HashMap<PathBuf, PackageJsonRc>> = RefCell::new(HashMap::new());
+  static TOLERANT_LINK_CACHE: RefCell<HashMap<PathBuf, PackageJsonRc>> = RefCell::new(HashMap::new());
+  // requested path -> path the package.json is cached under
+  static CACHE_KEYS: RefCell<HashMap<PathBuf, PathBuf>> = RefCell::new(HashMap::new());
 }
 
 pub struct PackageJsonThreadLocalCache;
@@ -20,39 +31,443 @@ pub struct PackageJsonThreadLocalCache;
 impl PackageJsonThreadLocalCache {
   pub fn clear() {
     CACHE.with(|cache| cache.borrow_mut().clear());
+    RAW_CACHE.with(|cache| cache.borrow_mut().clear());
+    TOLERANT_CACHE.with(|cache| cache.borrow_mut().clear());
+    TOLERANT_RAW_CACHE.with(|cache| cache.borrow_mut().clear());
+    LINK_CACHE.with(|cache| cache.borrow_mut().clear());
+    TOLERANT_LINK_CACHE.with(|cache| cache.borrow_mut().clear());
+    CACHE_KEYS.with(|keys| keys.borrow_mut().clear());
   }
 }
 
-impl deno_config::package_json::PackageJsonCache
-  for PackageJsonThreadLocalCache
-{
-  fn get(&self, path: &Path) -> Option<PackageJsonRc> {
-    CACHE.with(|cache| cache.borrow().get(path).cloned())
-  }
+const BOM_CHAR: char = '\u{FEFF}';
+
+/// Reads the package.json at `path`, stripping the byte order mark some
+/// editors on Windows add, which would otherwise fail to parse. When
+/// `tolerant` is set, comments and trailing commas are rewritten into strict
+/// JSON as well.
+fn read_pkg_json_text(
+  fs: &dyn deno_fs::FileSystem,
+  path: &Path,
+  tolerant: bool,
+) -> Result<String, std::io::Error> {
+  let text = fs
+    .read_text_file_lossy_sync(path, None)
+    .map_err(|err| err.into_io_error())?;
+  let text = match text.strip_prefix(BOM_CHAR) {
+    Some(text) => text.to_string(),
+    None => text,
+  };
+  Ok(match tolerant {
+    true => to_strict_json(text),
+    false => text,
+  })
+}
 
-  fn set(&self, path: PathBuf, package_json: PackageJsonRc) {
-    CACHE.with(|cache| cache.borrow_mut().insert(path, package_json));
+/// Rewrites JSON containing comments and trailing commas, as emitted by
+/// some monorepo tooling, into strict JSON. Text that can't be parsed is
+/// returned as is so the strict parser reports the error.
+///
+/// This is only used when tolerant loading was opted into with
+/// `NodeResolver::with_tolerant_package_json`, so by default
+/// package.json files are parsed as spec compliant JSON.
+fn to_strict_json(text: String) -> String {
+  let options = jsonc_parser::ParseOptions {
+    allow_comments: true,
+    allow_loose_object_property_names: false,
+    allow_trailing_commas: true,
+  };
+  match jsonc_parser::parse_to_serde_value(&text, &options) {
+    Ok(Some(value)) => value.to_string(),
+    Ok(None) | Err(_) => text,
   }
 }
 
 /// Helper to load a package.json file using the thread local cache
 /// in deno_node.
+///
+/// The cache is keyed on the canonicalized path, so a package.json reached
+/// through several symlinked paths is only loaded once. The returned
+/// package.json still reports the path it was requested with, and is cached
+/// under that path as well.
 pub fn load_pkg_json(
   fs: &dyn deno_fs::FileSystem,
   path: &Path,
 ) -> Result<Option<PackageJsonRc>, PackageJsonLoadError> {
-  let result = PackageJson::load_from_path(
-    path,
-    &DenoConfigFsAdapter::new(fs),
-    Some(&PackageJsonThreadLocalCache),
-  );
-  match result {
-    Ok(pkg_json) => Ok(Some(pkg_json)),
-    Err(PackageJsonLoadError::Io { source, .. })
-      if source.kind() == ErrorKind::NotFound =>
-    {
-      Ok(None)
+  load_pkg_json_inner(fs, path, false)
+}
+
+/// Like `load_pkg_json`, but the package.json may contain comments and
+/// trailing commas.
+pub fn load_pkg_json_tolerant(
+  fs: &dyn deno_fs::FileSystem,
+  path: &Path,
+) -> Result<Option<PackageJsonRc>, PackageJsonLoadError> {
+  load_pkg_json_inner(fs, path, true)
+}
+
+fn load_pkg_json_inner(
+  fs: &dyn deno_fs::FileSystem,
+  path: &Path,
+  tolerant: bool,
+) -> Result<Option<PackageJsonRc>, PackageJsonLoadError> {
+  let Some(key) = cache_key(fs, path) else {
+    return Ok(None);
+  };
+  let cache = match tolerant {
+    true => &TOLERANT_CACHE,
+    false => &CACHE,
+  };
+  let pkg_json = match cache.with(|cache| cache.borrow().get(&key).cloned()) {
+    Some(pkg_json) => pkg_json,
+    None => {
+      let Some(value) = load_raw(fs, &key, tolerant)? else {
+        return Ok(None);
+      };
+      let pkg_json = PackageJsonRc::new(PackageJson::load_from_value(
+        key.clone(),
+        (*value).clone(),
+      ));
+      cache.with(|cache| cache.borrow_mut().insert(key, pkg_json.clone()));
+      pkg_json
     }
-    Err(err) => Err(err),
+  };
+  if pkg_json.path == path {
+    return Ok(Some(pkg_json));
+  }
+  let link_cache = match tolerant {
+    true => &TOLERANT_LINK_CACHE,
+    false => &LINK_CACHE,
+  };
+  if let Some(pkg_json) =
+    link_cache.with(|cache| cache.borrow().get(path).cloned())
+  {
+    return Ok(Some(pkg_json));
+  }
+  let mut pkg_json = (*pkg_json).clone();
+  pkg_json.path = path.to_path_buf();
+  let pkg_json = PackageJsonRc::new(pkg_json);
+  link_cache.with(|cache| {
+    cache
+      .borrow_mut()
+      .insert(path.to_path_buf(), pkg_json.clone())
+  });
+  Ok(Some(pkg_json))
+}
+
+/// Helper to load the raw JSON of a package.json file, for reading fields
+/// that aren't modelled by `PackageJson`. The JSON is shared with
+/// `load_pkg_json`, so the file is read and parsed once and fails with the
+/// same errors.
+pub fn load_pkg_json_value(
+  fs: &dyn deno_fs::FileSystem,
+  path: &Path,
+  tolerant: bool,
+) -> Result<Option<Rc<Value>>, PackageJsonLoadError> {
+  let Some(key) = cache_key(fs, path) else {
+    return Ok(None);
+  };
+  load_raw(fs, &key, tolerant)
+}
+
+/// Reads and parses the package.json cached under `key`.
+fn load_raw(
+  fs: &dyn deno_fs::FileSystem,
+  key: &Path,
+  tolerant: bool,
+) -> Result<Option<Rc<Value>>, PackageJsonLoadError> {
+  let raw_cache = match tolerant {
+    true => &TOLERANT_RAW_CACHE,
+    false => &RAW_CACHE,
+  };
+  if let Some(value) = raw_cache.with(|cache| cache.borrow().get(key).cloned())
+  {
+    return Ok(Some(value));
+  }
+  let text = match read_pkg_json_text(fs, key, tolerant) {
+    Ok(text) => text,
+    Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+    Err(source) => {
+      return Err(PackageJsonLoadError::Io {
+        path: key.to_path_buf(),
+        source,
+      })
+    }
+  };
+  // like deno_config, an empty package.json is treated as an empty object
+  let value = if text.trim().is_empty() {
+    Value::Object(Default::default())
+  } else {
+    serde_json::from_str(&text).map_err(|source| {
+      PackageJsonLoadError::Deserialize {
+        path: key.to_path_buf(),
+        source,
+      }
+    })?
+  };
+  let value = Rc::new(value);
+  raw_cache
+    .with(|cache| cache.borrow_mut().insert(key.to_path_buf(), value.clone()));
+  Ok(Some(value))
+}
+
+/// Gets the path the package.json at `path` is cached under, which has all
+/// symlinks resolved, so a package.json reached through several paths only
+/// has a single cache entry. `load_pkg_json` still reports the requested
+/// path, as exports and main are resolved relative to its directory.
+///
+/// Returns `None` when there's no file at `path`. Most lookups are for a
+/// package.json that doesn't exist, so that's answered by a single stat and
+/// only existing files are canonicalized, once.
+fn cache_key(fs: &dyn deno_fs::FileSystem, path: &Path) -> Option<PathBuf> {
+  if let Some(key) = CACHE_KEYS.with(|keys| keys.borrow().get(path).cloned()) {
+    return Some(key);
+  }
+  if !fs.is_file_sync(path) {
+    return None;
+  }
+  let key = match fs.realpath_sync(path) {
+    Ok(key) => deno_core::strip_unc_prefix(key),
+    // ex. a virtual file system that doesn't resolve symlinks
+    Err(_) => path.to_path_buf(),
+  };
+  CACHE_KEYS
+    .with(|keys| keys.borrow_mut().insert(path.to_path_buf(), key.clone()));
+  Some(key)
+}
+
+#[cfg(test)]
+mod test {
+  use deno_fs::RealFs;
+
+  use super::*;
+  use crate::test_util::in_memory_fs;
+
+  #[cfg(unix)]
+  #[test]
+  fn load_pkg_json_symlinked_paths_share_cache_entry() {
+    use deno_fs::FileSystem;
+    use deno_fs::OpenOptions;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let root = RealFs.realpath_sync(temp_dir.path()).unwrap();
+    let pkg_dir = root.join("pkg");
+    RealFs.mkdir_sync(&pkg_dir, false, 0o755).unwrap();
+    RealFs
+      .write_file_sync(
+        &pkg_dir.join("package.json"),
+        OpenOptions::write(true, false, false, None),
+        None,
+        br#"{ "name": "pkg" }"#,
+      )
+      .unwrap();
+    let link_dir = root.join("link");
+    RealFs.symlink_sync(&pkg_dir, &link_dir, None).unwrap();
+
+    PackageJsonThreadLocalCache::clear();
+    let from_link = load_pkg_json(&RealFs, &link_dir.join("package.json"))
+      .unwrap()
+      .unwrap();
+    let from_real = load_pkg_json(&RealFs, &pkg_dir.join("package.json"))
+      .unwrap()
+      .unwrap();
+
+    assert_eq!(from_link.name.as_deref(), Some("pkg"));
+    assert_eq!(from_link.path, link_dir.join("package.json"));
+    assert_eq!(from_real.path, pkg_dir.join("package.json"));
+    let from_link_again =
+      load_pkg_json(&RealFs, &link_dir.join("package.json"))
+        .unwrap()
+        .unwrap();
+    assert!(PackageJsonRc::ptr_eq(&from_link, &from_link_again));
+    CACHE.with(|cache| {
+      let cache = cache.borrow();
+      assert_eq!(cache.len(), 1);
+      assert!(cache.contains_key(&pkg_dir.join("package.json")));
+    });
+  }
+
+  #[test]
+  fn load_pkg_json_missing_file_is_not_canonicalized() {
+    let fs = in_memory_fs(&[("/app/main.js", "")]);
+
+    PackageJsonThreadLocalCache::clear();
+    assert!(load_pkg_json(&fs, Path::new("/app/package.json"))
+      .unwrap()
+      .is_none());
+    CACHE_KEYS.with(|keys| assert!(keys.borrow().is_empty()));
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn load_pkg_json_symlinked_file_shares_cache_entry() {
+    use deno_fs::FileSystem;
+    use deno_fs::OpenOptions;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let root = RealFs.realpath_sync(temp_dir.path()).unwrap();
+    let shared_dir = root.join("shared");
+    RealFs.mkdir_sync(&shared_dir, false, 0o755).unwrap();
+    RealFs
+      .write_file_sync(
+        &shared_dir.join("package.json"),
+        OpenOptions::write(true, false, false, None),
+        None,
+        br#"{ "exports": "./index.js" }"#,
+      )
+      .unwrap();
+    let a_dir = root.join("a");
+    let b_dir = root.join("b");
+    for dir in [&a_dir, &b_dir] {
+      RealFs.mkdir_sync(dir, false, 0o755).unwrap();
+      RealFs
+        .symlink_sync(
+          &shared_dir.join("package.json"),
+          &dir.join("package.json"),
+          None,
+        )
+        .unwrap();
+    }
+    let a_link = root.join("a_link");
+    RealFs.symlink_sync(&a_dir, &a_link, None).unwrap();
+
+    PackageJsonThreadLocalCache::clear();
+    let a = load_pkg_json(&RealFs, &a_dir.join("package.json"))
+      .unwrap()
+      .unwrap();
+    let b = load_pkg_json(&RealFs, &b_dir.join("package.json"))
+      .unwrap()
+      .unwrap();
+    let a_via_link = load_pkg_json(&RealFs, &a_link.join("package.json"))
+      .unwrap()
+      .unwrap();
+
+    // the shared file is cached once, but each package still resolves its
+    // exports relative to the directory it was requested from
+    assert_eq!(a.path, a_dir.join("package.json"));
+    assert_eq!(b.path, b_dir.join("package.json"));
+    assert_eq!(a_via_link.path, a_link.join("package.json"));
+    assert!(a.exports.is_some() && b.exports.is_some());
+    CACHE.with(|cache| {
+      let cache = cache.borrow();
+      assert_eq!(cache.len(), 1);
+      assert!(cache.contains_key(&shared_dir.join("package.json")));
+    });
+  }
+
+  #[test]
+  fn load_pkg_json_value_reads_unmodelled_fields() {
+    let fs = in_memory_fs(&[(
+      "/app/package.json",
+      r#"{ "name": "pkg", "engines": { "node": ">=18" } }"#,
+    )]);
+    let pkg_json_path = Path::new("/app/package.json");
+
+    PackageJsonThreadLocalCache::clear();
+    let value = load_pkg_json_value(&fs, pkg_json_path, false)
+      .unwrap()
+      .unwrap();
+    assert_eq!(value["engines"], serde_json::json!({ "node": ">=18" }));
+    assert!(value.get("sideEffects").is_none());
+    let cached = load_pkg_json_value(&fs, pkg_json_path, false)
+      .unwrap()
+      .unwrap();
+    assert!(Rc::ptr_eq(&value, &cached));
+    assert!(load_pkg_json_value(
+      &fs,
+      Path::new("/app/missing/package.json"),
+      false
+    )
+    .unwrap()
+    .is_none());
+  }
+
+  #[test]
+  fn load_pkg_json_value_shares_the_parsed_json() {
+    let fs = in_memory_fs(&[
+      ("/app/package.json", r#"{ "name": "pkg" }"#),
+      // a malformed file fails the same way for both
+      ("/malformed/package.json", r#"{ "name": "pkg", }"#),
+    ]);
+    let pkg_json_path = Path::new("/app/package.json");
+
+    PackageJsonThreadLocalCache::clear();
+    load_pkg_json(&fs, pkg_json_path).unwrap().unwrap();
+    RAW_CACHE.with(|cache| assert_eq!(cache.borrow().len(), 1));
+    let value = load_pkg_json_value(&fs, pkg_json_path, false)
+      .unwrap()
+      .unwrap();
+    assert_eq!(value["name"], "pkg");
+    RAW_CACHE.with(|cache| assert_eq!(cache.borrow().len(), 1));
+
+    let malformed_path = Path::new("/malformed/package.json");
+    assert!(matches!(
+      load_pkg_json_value(&fs, malformed_path, false),
+      Err(PackageJsonLoadError::Deserialize { .. })
+    ));
+    assert!(matches!(
+      load_pkg_json(&fs, malformed_path),
+      Err(PackageJsonLoadError::Deserialize { .. })
+    ));
+  }
+
+  #[test]
+  fn load_pkg_json_from_virtual_fs() {
+    // nothing exists at this path on disk, so it can only be read through
+    // the provided file system
+    let pkg_json_path = PathBuf::from("/virtual/node_modules/pkg/package.json");
+    let fs = in_memory_fs(&[(
+      "/virtual/node_modules/pkg/package.json",
+      r#"{ "name": "pkg", "version": "1.0.0", "sideEffects": false }"#,
+    )]);
+
+    PackageJsonThreadLocalCache::clear();
+    let pkg_json = load_pkg_json(&fs, &pkg_json_path).unwrap().unwrap();
+    assert_eq!(pkg_json.name.as_deref(), Some("pkg"));
+    assert_eq!(pkg_json.version.as_deref(), Some("1.0.0"));
+    let value = load_pkg_json_value(&fs, &pkg_json_path, false)
+      .unwrap()
+      .unwrap();
+    assert_eq!(value["sideEffects"], false);
+
+    PackageJsonThreadLocalCache::clear();
+    assert!(load_pkg_json(&RealFs, &pkg_json_path).unwrap().is_none());
+  }
+
+  #[test]
+  fn load_pkg_json_strips_bom() {
+    let fs =
+      in_memory_fs(&[("/app/package.json", "\u{FEFF}{ \"name\": \"pkg\" }")]);
+    let pkg_json_path = Path::new("/app/package.json");
+
+    PackageJsonThreadLocalCache::clear();
+    let pkg_json = load_pkg_json(&fs, pkg_json_path).unwrap().unwrap();
+    assert_eq!(pkg_json.name.as_deref(), Some("pkg"));
+    let value = load_pkg_json_value(&fs, pkg_json_path, false)
+      .unwrap()
+      .unwrap();
+    assert_eq!(value["name"], "pkg");
+  }
+
+  #[test]
+  fn load_pkg_json_tolerant_mode() {
+    let fs = in_memory_fs(&[(
+      "/app/package.json",
+      "{\n  // generated\n  \"name\": \"pkg\",\n}\n",
+    )]);
+    let pkg_json_path = Path::new("/app/package.json");
+
+    PackageJsonThreadLocalCache::clear();
+    assert!(load_pkg_json(&fs, pkg_json_path).is_err());
+    assert!(load_pkg_json_value(&fs, pkg_json_path, false).is_err());
+
+    let pkg_json = load_pkg_json_tolerant(&fs, pkg_json_path);
+    let value = load_pkg_json_value(&fs, pkg_json_path, true);
+    assert_eq!(pkg_json.unwrap().unwrap().name.as_deref(), Some("pkg"));
+    assert_eq!(value.unwrap().unwrap()["name"], "pkg");
+
+    // strict loads don't reuse the tolerant results
+    assert!(load_pkg_json(&fs, pkg_json_path).is_err());
+    assert!(load_pkg_json_value(&fs, pkg_json_path, false).is_err());
   }
 }
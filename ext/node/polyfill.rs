@@ -1,5 +1,7 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
+use std::collections::HashMap;
+
 use deno_core::ModuleSpecifier;
 
 /// e.g. `is_builtin_node_module("assert")`
@@ -21,6 +23,60 @@ pub fn get_module_name_from_builtin_node_module_specifier(
   Some(specifier)
 }
 
+/// A builtin module registered by the embedder, either in addition to or
+/// in place of one of `SUPPORTED_BUILTIN_NODE_MODULES`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeModulePolyfill {
+  /// Name of the module like "sqlite" or "timers/promises".
+  pub name: String,
+  /// Specifier of the module that implements it. `require()` loads it
+  /// lazily, so the embedder's extension has to list it in
+  /// `lazy_loaded_esm`.
+  pub specifier: String,
+}
+
+/// The builtin modules available to a runtime: the polyfills shipped with
+/// this crate merged with the ones registered by the embedder. On a name
+/// collision the embedder's polyfill wins.
+#[derive(Debug, Clone, Default)]
+pub struct BuiltinNodeModules {
+  extra: HashMap<String, NodeModulePolyfill>,
+}
+
+impl BuiltinNodeModules {
+  pub fn new(extra_polyfills: Vec<NodeModulePolyfill>) -> Self {
+    let mut builtins = Self::default();
+    builtins.extend(extra_polyfills);
+    builtins
+  }
+
+  /// Registers more polyfills, replacing any registered earlier under the
+  /// same name.
+  pub fn extend(&mut self, extra_polyfills: Vec<NodeModulePolyfill>) {
+    self.extra.extend(
+      extra_polyfills
+        .into_iter()
+        .map(|polyfill| (polyfill.name.clone(), polyfill)),
+    );
+  }
+
+  pub fn is_builtin(&self, module_name: &str) -> bool {
+    self.extra.contains_key(module_name) || is_builtin_node_module(module_name)
+  }
+
+  /// Returns the specifier of the module implementing `module_name`, which
+  /// is `node:<module_name>` unless the embedder provides it.
+  pub fn specifier(&self, module_name: &str) -> Option<String> {
+    match self.extra.get(module_name) {
+      Some(polyfill) => Some(polyfill.specifier.clone()),
+      None if is_builtin_node_module(module_name) => {
+        Some(format!("node:{module_name}"))
+      }
+      None => None,
+    }
+  }
+}
+
 macro_rules! generate_builtin_node_module_lists {
   ($( $module_name:literal ,)+) => {
     pub static SUPPORTED_BUILTIN_NODE_MODULES: &[&str] = &[
@@ -91,3 +147,39 @@ generate_builtin_node_module_lists! {
   "worker_threads",
   "zlib",
 }
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn builtin_node_modules_with_extra_polyfills() {
+    let builtins = BuiltinNodeModules::new(vec![
+      NodeModulePolyfill {
+        name: "sqlite".to_string(),
+        specifier: "ext:embedder/sqlite.js".to_string(),
+      },
+      NodeModulePolyfill {
+        name: "fs".to_string(),
+        specifier: "ext:embedder/fs.js".to_string(),
+      },
+    ]);
+    assert!(builtins.is_builtin("sqlite"));
+    assert!(builtins.is_builtin("path"));
+    assert!(!builtins.is_builtin("lodash"));
+    assert_eq!(
+      builtins.specifier("sqlite").as_deref(),
+      Some("ext:embedder/sqlite.js")
+    );
+    // the embedder's polyfill overrides the one shipped with deno_node
+    assert_eq!(
+      builtins.specifier("fs").as_deref(),
+      Some("ext:embedder/fs.js")
+    );
+    assert_eq!(builtins.specifier("path").as_deref(), Some("node:path"));
+    assert_eq!(builtins.specifier("lodash"), None);
+
+    let builtins = BuiltinNodeModules::default();
+    assert!(!builtins.is_builtin("sqlite"));
+  }
+}
@@ -6,27 +6,34 @@ import { core, internals, primordials } from "ext:core/mod.js";
 import {
   op_napi_open,
   op_require_as_file_path,
+  op_require_assert_cjs,
   op_require_break_on_next_statement,
+  op_require_builtin_module_specifier,
+  op_require_cache_key,
+  op_require_create_require_context,
   op_require_init_paths,
   op_require_is_deno_dir_package,
   op_require_is_request_relative,
+  op_require_module_exists,
   op_require_node_module_paths,
   op_require_package_imports_resolve,
   op_require_path_basename,
   op_require_path_dirname,
   op_require_path_is_absolute,
   op_require_path_resolve,
-  op_require_proxy_path,
-  op_require_read_closest_package_json,
   op_require_read_file,
   op_require_read_package_scope,
   op_require_real_path,
+  op_require_resolve_absolute_package,
   op_require_resolve_deno_dir,
   op_require_resolve_exports,
   op_require_resolve_lookup_paths,
   op_require_stat,
+  op_require_stat_batch,
+  op_require_take_resolution_warnings,
   op_require_try_self,
   op_require_try_self_parent_path,
+  op_require_validate_specifier,
 } from "ext:core/ops";
 const {
   ArrayIsArray,
@@ -279,6 +286,9 @@ function pathResolve(...args) {
 }
 
 const nativeModulePolyfill = new SafeMap();
+// Specifiers of the builtins registered by the embedder, keyed by name.
+// `null` marks names that aren't provided by the embedder.
+const extraPolyfillSpecifiers = new SafeMap();
 
 const relativeResolveCache = ObjectCreate(null);
 let requireDepth = 0;
@@ -306,6 +316,34 @@ function stat(filename) {
   return result;
 }
 
+/** Like `stat`, but the paths that aren't cached are stat'd with a single op
+ * call. */
+function statBatch(filenames) {
+  const results = [];
+  const uncached = [];
+  for (let i = 0; i < filenames.length; i++) {
+    const result = statCache?.get(filenames[i]);
+    ArrayPrototypePush(results, result);
+    if (result === undefined) {
+      ArrayPrototypePush(uncached, filenames[i]);
+    }
+  }
+  if (uncached.length === 0) {
+    return results;
+  }
+  const uncachedResults = op_require_stat_batch(uncached);
+  let j = 0;
+  for (let i = 0; i < filenames.length; i++) {
+    if (results[i] !== undefined) continue;
+    const result = uncachedResults[j++];
+    results[i] = result;
+    if (statCache !== null && result >= 0) {
+      statCache.set(filenames[i], result);
+    }
+  }
+  return results;
+}
+
 function updateChildren(parent, child, scan) {
   if (!parent) {
     return;
@@ -387,12 +425,16 @@ function toRealPath(requestPath) {
   return rp;
 }
 
-function tryExtensions(p, exts, isMain) {
+function tryExtensions(p, exts, _isMain) {
+  const filenames = [];
   for (let i = 0; i < exts.length; i++) {
-    const filename = tryFile(p + exts[i], isMain);
-
-    if (filename) {
-      return filename;
+    ArrayPrototypePush(filenames, p + exts[i]);
+  }
+  // every candidate is stat'd at once rather than one op call per extension
+  const results = statBatch(filenames);
+  for (let i = 0; i < filenames.length; i++) {
+    if (results[i] === 0) {
+      return toRealPath(filenames[i]);
     }
   }
   return false;
@@ -511,14 +553,27 @@ function resolveExports(
     return false;
   }
 
-  return op_require_resolve_exports(
+  const resolved = op_require_resolve_exports(
     usesLocalNodeModulesDir,
     modulesPath,
     request,
     name,
     expansion,
     parentPath,
-  ) ?? false;
+  );
+  emitResolutionWarnings();
+  return resolved ?? false;
+}
+
+function emitResolutionWarnings() {
+  const warnings = op_require_take_resolution_warnings();
+  for (let i = 0; i < warnings.length; ++i) {
+    process.emitWarning(
+      warnings[i].message,
+      "DeprecationWarning",
+      warnings[i].code,
+    );
+  }
 }
 
 Module._findPath = function (request, paths, isMain, parentPath) {
@@ -579,6 +634,11 @@ Module._findPath = function (request, paths, isMain, parentPath) {
     let filename;
 
     const rc = stat(basePath);
+    if (absoluteRequest && rc === 1) {
+      // a package required by its absolute path is still resolved through
+      // its "exports"
+      filename = op_require_resolve_absolute_package(basePath, parentPath);
+    }
     if (!trailingSlash) {
       if (rc === 0) { // File.
         filename = toRealPath(basePath);
@@ -589,7 +649,14 @@ Module._findPath = function (request, paths, isMain, parentPath) {
         if (exts === undefined) {
           exts = ObjectKeys(Module._extensions);
         }
-        filename = tryExtensions(basePath, exts, isMain);
+        if (rc === 1) {
+          filename = tryExtensions(basePath, exts, isMain);
+        } else {
+          // nothing exists at `basePath`, so there's no directory index to
+          // fall back to and the extensions are probed in a single op
+          const found = op_require_module_exists(basePath, exts);
+          filename = found === null ? false : toRealPath(found);
+        }
       }
     }
 
@@ -746,6 +813,15 @@ Module._load = function (request, parent, isMain) {
   return module.exports;
 };
 
+// `require.cache` is keyed on the real path of a module, so a module reached
+// through a symlink, ex. by requiring its own package name, is only loaded
+// once.
+function toCacheKey(filename) {
+  return op_require_path_is_absolute(filename)
+    ? op_require_cache_key(filename)
+    : filename;
+}
+
 Module._resolveFilename = function (
   request,
   parent,
@@ -759,6 +835,9 @@ Module._resolveFilename = function (
     return request;
   }
 
+  // fail early for specifiers that can never resolve
+  op_require_validate_specifier(request);
+
   let paths;
 
   if (typeof options === "object" && options !== null) {
@@ -801,7 +880,7 @@ Module._resolveFilename = function (
         request,
       );
       if (maybeResolved) {
-        return maybeResolved;
+        return toCacheKey(maybeResolved);
       }
     }
   }
@@ -813,11 +892,13 @@ Module._resolveFilename = function (
     parent?.id,
   );
   const selfResolved = op_require_try_self(parentPath, request);
+  emitResolutionWarnings();
   if (selfResolved) {
     const cacheKey = request + "\x00" +
       (paths.length === 1 ? paths[0] : ArrayPrototypeJoin(paths, "\x00"));
-    Module._pathCache[cacheKey] = selfResolved;
-    return selfResolved;
+    const filename = toCacheKey(selfResolved);
+    Module._pathCache[cacheKey] = filename;
+    return filename;
   }
 
   // Look up the filename first, since that's the cache key.
@@ -828,7 +909,7 @@ Module._resolveFilename = function (
     parentPath,
   );
   if (filename) {
-    return op_require_real_path(filename);
+    return op_require_cache_key(filename);
   }
   const requireStack = [];
   for (let cursor = parent; cursor; cursor = moduleParentCache.get(cursor)) {
@@ -1052,17 +1133,9 @@ Module.prototype._compile = function (content, filename) {
 
 Module._extensions[".js"] = function (module, filename) {
   const content = op_require_read_file(filename);
-
-  if (StringPrototypeEndsWith(filename, ".js")) {
-    const pkg = op_require_read_closest_package_json(filename);
-    if (pkg && pkg.typ === "module") {
-      throw createRequireEsmError(
-        filename,
-        moduleParentCache.get(module)?.filename,
-      );
-    }
-  }
-
+  // throws ERR_REQUIRE_ESM when `filename` is a `.js` file in a package
+  // whose "type" is "module"
+  op_require_assert_cjs(filename, moduleParentCache.get(module)?.filename);
   module._compile(content, filename);
 };
 
@@ -1080,6 +1153,15 @@ function createRequireEsmError(filename, parent) {
   return err;
 }
 
+core.registerErrorBuilder(
+  "ERR_REQUIRE_ESM",
+  function ERR_REQUIRE_ESM(msg) {
+    const err = new Error(msg);
+    err.code = "ERR_REQUIRE_ESM";
+    return err;
+  },
+);
+
 function stripBOM(content) {
   if (StringPrototypeCharCodeAt(content, 0) === 0xfeff) {
     content = StringPrototypeSlice(content, 1);
@@ -1113,10 +1195,10 @@ Module._extensions[".node"] = function (module, filename) {
 };
 
 function createRequireFromPath(filename) {
-  const proxyPath = op_require_proxy_path(filename);
-  const mod = new Module(proxyPath);
-  mod.filename = proxyPath;
-  mod.paths = Module._nodeModulePaths(mod.path);
+  const context = op_require_create_require_context(filename);
+  const mod = new Module(context.filename);
+  mod.filename = context.filename;
+  mod.paths = context.paths;
   return makeRequireFunction(mod);
 }
 
@@ -1193,6 +1275,10 @@ function isBuiltin(moduleName) {
     return false;
   }
 
+  if (extraPolyfillSpecifier(moduleName) !== null) {
+    return true;
+  }
+
   return moduleName in nativeModuleExports &&
     !StringPrototypeStartsWith(moduleName, "internal/");
 }
@@ -1220,11 +1306,51 @@ Module.Module = Module;
 
 nativeModuleExports.module = Module;
 
+/**
+ * Returns the specifier of the polyfill the embedder registered for `name`,
+ * or `null` if there is none.
+ * @param name {string}
+ */
+function extraPolyfillSpecifier(name) {
+  if (extraPolyfillSpecifiers.has(name)) {
+    return extraPolyfillSpecifiers.get(name);
+  }
+  let specifier = null;
+  if (
+    !StringPrototypeStartsWith(name, ".") &&
+    !StringPrototypeStartsWith(name, "/")
+  ) {
+    specifier = op_require_builtin_module_specifier(name);
+    // stock builtins are reported as "node:<name>" and are already part
+    // of `nativeModuleExports`
+    if (specifier !== null && StringPrototypeStartsWith(specifier, "node:")) {
+      specifier = null;
+    }
+  }
+  extraPolyfillSpecifiers.set(name, specifier);
+  return specifier;
+}
+
+/**
+ * Returns the exports of the polyfill the embedder registered for `name`,
+ * or `undefined` if there is none. Polyfills registered in place of a stock
+ * builtin take precedence over it.
+ * @param name {string}
+ */
+function loadExtraPolyfill(name) {
+  const specifier = extraPolyfillSpecifier(name);
+  if (specifier === null) {
+    return undefined;
+  }
+  const ns = core.createLazyLoader(specifier)();
+  return ns.default ?? ns;
+}
+
 function loadNativeModule(_id, request) {
   if (nativeModulePolyfill.has(request)) {
     return nativeModulePolyfill.get(request);
   }
-  const modExports = nativeModuleExports[request];
+  const modExports = loadExtraPolyfill(request) ?? nativeModuleExports[request];
   if (modExports) {
     const nodeMod = new Module(request);
     nodeMod.exports = modExports;
@@ -1236,7 +1362,8 @@ function loadNativeModule(_id, request) {
 }
 
 function nativeModuleCanBeRequiredByUsers(request) {
-  return !!nativeModuleExports[request];
+  return extraPolyfillSpecifier(request) !== null ||
+    !!nativeModuleExports[request];
 }
 
 function readPackageScope() {
@@ -3,6 +3,7 @@
 // deno-lint-ignore-file
 
 import { internals } from "ext:core/mod.js";
+import { op_require_node_options_preloads } from "ext:core/ops";
 const requireImpl = internals.requireImpl;
 
 import { nodeGlobals } from "ext:deno_node/00_globals.js";
@@ -43,6 +44,9 @@ function initialize(args) {
       maybeWorkerMetadata,
     );
     internals.__setupChildProcessIpcChannel();
+    // `NODE_OPTIONS=--require` modules run before the main module, whether
+    // it's a CommonJS or an ES module
+    requireImpl.Module._preloadModules(op_require_node_options_preloads());
     // `Deno[Deno.internal].requireImpl` will be unreachable after this line.
     delete internals.requireImpl;
   } else {
@@ -3,6 +3,7 @@
 // TODO(petamoriken): enable prefer-primordials for node polyfills
 // deno-lint-ignore-file prefer-primordials
 
+import { op_node_copy_file } from "ext:core/ops";
 import type { CallbackWithError } from "ext:deno_node/_fs/_fs_common.ts";
 import { makeCallback } from "ext:deno_node/_fs/_fs_common.ts";
 import { Buffer } from "node:buffer";
@@ -12,6 +13,7 @@ import {
 } from "ext:deno_node/internal/fs/utils.mjs";
 import { fs } from "ext:deno_node/internal_binding/constants.ts";
 import { codeMap } from "ext:deno_node/internal_binding/uv.ts";
+import { denoErrorToNodeError } from "ext:deno_node/internal/errors.ts";
 import { promisify } from "ext:deno_node/internal/util.mjs";
 
 export function copyFile(
@@ -76,17 +78,13 @@ export function copyFileSync(
   const destStr = getValidatedPath(dest, "dest").toString();
   const modeNum = getValidMode(mode, "copyFile");
 
-  if ((modeNum & fs.COPYFILE_EXCL) === fs.COPYFILE_EXCL) {
-    try {
-      Deno.lstatSync(destStr);
-      throw new Error(`A file exists at the destination: ${destStr}`);
-    } catch (e) {
-      if (e instanceof Deno.errors.NotFound) {
-        Deno.copyFileSync(srcStr, destStr);
-      }
-      throw e;
-    }
-  } else {
-    Deno.copyFileSync(srcStr, destStr);
+  try {
+    op_node_copy_file(srcStr, destStr, modeNum);
+  } catch (err) {
+    throw denoErrorToNodeError(err as Error, {
+      syscall: "copyfile",
+      path: srcStr,
+      dest: destStr,
+    });
   }
 }
@@ -3,16 +3,19 @@
 // TODO(petamoriken): enable prefer-primordials for node polyfills
 // deno-lint-ignore-file prefer-primordials
 
+import { op_node_fs_lstat } from "ext:core/ops";
 import { denoErrorToNodeError } from "ext:deno_node/internal/errors.ts";
 import {
   BigIntStats,
   CFISBIS,
+  convertNodeStats,
   statCallback,
   statCallbackBigInt,
   statOptions,
   Stats,
 } from "ext:deno_node/_fs/_fs_stat.ts";
 import { promisify } from "ext:deno_node/internal/util.mjs";
+import { pathFromURL } from "ext:deno_web/00_infra.js";
 
 export function lstat(path: string | URL, callback: statCallback): void;
 export function lstat(
@@ -67,9 +70,10 @@ export function lstatSync(
   path: string | URL,
   options?: statOptions,
 ): Stats | BigIntStats {
+  const bigint = options?.bigint || false;
   try {
-    const origin = Deno.lstatSync(path);
-    return CFISBIS(origin, options?.bigint || false);
+    const origin = op_node_fs_lstat(pathFromURL(path), bigint);
+    return convertNodeStats(origin, bigint);
   } catch (err) {
     if (
       options?.throwIfNoEntry === false &&
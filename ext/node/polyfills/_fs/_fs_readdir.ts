@@ -3,6 +3,7 @@
 // TODO(petamoriken): enable prefer-primordials for node polyfills
 // deno-lint-ignore-file prefer-primordials
 
+import { op_node_readdir } from "ext:core/ops";
 import { TextDecoder, TextEncoder } from "ext:deno_web/08_text_encoding.js";
 import { asyncIterableToCallback } from "ext:deno_node/_fs/_fs_watch.ts";
 import Dirent from "ext:deno_node/_fs/_fs_dirent.ts";
@@ -15,6 +16,22 @@ function toDirent(val: Deno.DirEntry): Dirent {
   return new Dirent(val);
 }
 
+/** An entry listed by `op_node_readdir`, `type` is only set when the types
+ * were requested. */
+interface NodeDirEntry {
+  name: string;
+  type?: "file" | "dir" | "symlink" | "unknown";
+}
+
+function nodeDirEntryToDirent(entry: NodeDirEntry): Dirent {
+  return new Dirent({
+    name: entry.name,
+    isFile: entry.type === "file",
+    isDirectory: entry.type === "dir",
+    isSymlink: entry.type === "symlink",
+  });
+}
+
 type readDirOptions = {
   encoding?: string;
   withFileTypes?: boolean;
@@ -129,11 +146,16 @@ export function readdirSync(
     }
   }
 
+  const withFileTypes = options?.withFileTypes || false;
   try {
-    for (const file of Deno.readDirSync(path.toString())) {
-      if (options?.withFileTypes) {
-        result.push(toDirent(file));
-      } else result.push(decode(file.name));
+    const entries: NodeDirEntry[] = op_node_readdir(
+      path.toString(),
+      withFileTypes,
+    );
+    for (const entry of entries) {
+      if (withFileTypes) {
+        result.push(nodeDirEntryToDirent(entry));
+      } else result.push(decode(entry.name));
     }
   } catch (e) {
     throw denoErrorToNodeError(e as Error, { syscall: "readdir" });
@@ -3,6 +3,11 @@
 // TODO(petamoriken): enable prefer-primordials for node polyfills
 // deno-lint-ignore-file prefer-primordials
 
+import {
+  op_node_fs_realpath,
+  op_node_fs_realpath_async,
+} from "ext:core/ops";
+import { denoErrorToNodeError } from "ext:deno_node/internal/errors.ts";
 import { promisify } from "ext:deno_node/internal/util.mjs";
 
 type Options = { encoding: string };
@@ -25,7 +30,23 @@ export function realpath(
   );
 }
 
-realpath.native = realpath;
+realpath.native = function (
+  path: string,
+  options?: Options | Callback,
+  callback?: Callback,
+) {
+  if (typeof options === "function") {
+    callback = options;
+  }
+  if (!callback) {
+    throw new Error("No callback function supplied");
+  }
+  op_node_fs_realpath_async(path).then(
+    (resolved: string) => callback!(null, resolved),
+    (err: Error) =>
+      callback!(denoErrorToNodeError(err, { syscall: "realpath", path })),
+  );
+};
 
 export const realpathPromise = promisify(realpath) as (
   path: string,
@@ -36,4 +57,10 @@ export function realpathSync(path: string): string {
   return Deno.realPathSync(path);
 }
 
-realpathSync.native = realpathSync;
+realpathSync.native = function (path: string): string {
+  try {
+    return op_node_fs_realpath(path);
+  } catch (err) {
+    throw denoErrorToNodeError(err as Error, { syscall: "realpath", path });
+  }
+};
@@ -3,9 +3,11 @@
 // TODO(petamoriken): enable prefer-primordials for node polyfills
 // deno-lint-ignore-file prefer-primordials
 
+import { op_node_fs_stat } from "ext:core/ops";
 import { denoErrorToNodeError } from "ext:deno_node/internal/errors.ts";
 import { promisify } from "ext:deno_node/internal/util.mjs";
 import { primordials } from "ext:core/mod.js";
+import { pathFromURL } from "ext:deno_web/00_infra.js";
 
 const { ObjectCreate, ObjectAssign } = primordials;
 
@@ -349,6 +351,79 @@ export function CFISBIS(fileInfo: Deno.FileInfo, bigInt: boolean) {
   return convertFileInfoToStats(fileInfo);
 }
 
+/** The stats returned by `op_node_fs_stat` and `op_node_fs_lstat`. The
+ * numbers of the bigint variant are strings. */
+export interface NodeStatsObject {
+  dev: number | string;
+  ino: number | string;
+  mode: number | string;
+  nlink: number | string;
+  uid: number | string;
+  gid: number | string;
+  rdev: number | string;
+  size: number | string;
+  blksize: number | string;
+  blocks: number | string;
+  atimeMs: number | string;
+  mtimeMs: number | string;
+  ctimeMs: number | string;
+  birthtimeMs: number | string;
+  isFile: boolean;
+  isDirectory: boolean;
+  isSymbolicLink: boolean;
+  isBlockDevice: boolean;
+  isCharacterDevice: boolean;
+  isFIFO: boolean;
+  isSocket: boolean;
+}
+
+export function convertNodeStats(
+  origin: NodeStatsObject,
+  bigInt: boolean,
+): Stats | BigIntStats {
+  const toValue = bigInt ? BigInt : Number;
+  const stats = ObjectCreate(
+    bigInt ? BigIntStats.prototype : Stats.prototype,
+  );
+  ObjectAssign(stats, {
+    dev: toValue(origin.dev),
+    ino: toValue(origin.ino),
+    mode: toValue(origin.mode),
+    nlink: toValue(origin.nlink),
+    uid: toValue(origin.uid),
+    gid: toValue(origin.gid),
+    rdev: toValue(origin.rdev),
+    size: toValue(origin.size),
+    blksize: toValue(origin.blksize),
+    blocks: toValue(origin.blocks),
+    atimeMs: toValue(origin.atimeMs),
+    mtimeMs: toValue(origin.mtimeMs),
+    ctimeMs: toValue(origin.ctimeMs),
+    birthtimeMs: toValue(origin.birthtimeMs),
+    atime: dateFromMs(origin.atimeMs),
+    mtime: dateFromMs(origin.mtimeMs),
+    ctime: dateFromMs(origin.ctimeMs),
+    birthtime: dateFromMs(origin.birthtimeMs),
+    isFile: () => origin.isFile,
+    isDirectory: () => origin.isDirectory,
+    isSymbolicLink: () => origin.isSymbolicLink,
+    isBlockDevice: () => origin.isBlockDevice,
+    isFIFO: () => origin.isFIFO,
+    isCharacterDevice: () => origin.isCharacterDevice,
+    isSocket: () => origin.isSocket,
+  });
+  if (bigInt) {
+    // only millisecond precision is available
+    ObjectAssign(stats, {
+      atimeNs: stats.atimeMs * 1000000n,
+      mtimeNs: stats.mtimeMs * 1000000n,
+      ctimeNs: stats.ctimeMs * 1000000n,
+      birthtimeNs: stats.birthtimeMs * 1000000n,
+    });
+  }
+  return stats;
+}
+
 export type statCallbackBigInt = (err: Error | null, stat: BigIntStats) => void;
 
 export type statCallback = (err: Error | null, stat: Stats) => void;
@@ -406,9 +481,10 @@ export function statSync(
   path: string | URL,
   options: statOptions = { bigint: false, throwIfNoEntry: true },
 ): Stats | BigIntStats | undefined {
+  const bigint = options?.bigint || false;
   try {
-    const origin = Deno.statSync(path);
-    return CFISBIS(origin, options.bigint);
+    const origin = op_node_fs_stat(pathFromURL(path), bigint);
+    return convertNodeStats(origin, bigint);
   } catch (err) {
     if (
       options?.throwIfNoEntry === false &&
@@ -3,7 +3,8 @@
 // TODO(petamoriken): enable prefer-primordials for node polyfills
 // deno-lint-ignore-file prefer-primordials
 
-import { basename } from "node:path";
+import { core } from "ext:core/mod.js";
+import { op_node_fs_watch, op_node_fs_watch_next } from "ext:core/ops";
 import { EventEmitter } from "node:events";
 import { notImplemented } from "ext:deno_node/_utils.ts";
 import { promisify } from "node:util";
@@ -114,39 +115,53 @@ export function watch(
 
   const watchPath = getValidatedPath(filename).toString();
 
-  let iterator: Deno.FsWatcher;
+  let rid: number | undefined;
+  let promise: Promise<NodeFsWatchEvent | null> | undefined;
+  let refed = true;
   // Start the actual watcher a few msec later to avoid race condition
   // error in test case in compat test case
   // (parallel/test-fs-watch.js, parallel/test-fs-watchfile.js)
-  const timer = setTimeout(() => {
-    iterator = Deno.watchFs(watchPath, {
-      recursive: options?.recursive || false,
-    });
-
-    asyncIterableToCallback<Deno.FsEvent>(iterator, (val, done) => {
-      if (done) return;
-      fsWatcher.emit(
-        "change",
-        convertDenoFsEventToNodeFsEvent(val.kind),
-        basename(val.paths[0]),
-      );
-    }, (e) => {
+  const timer = setTimeout(async () => {
+    try {
+      rid = op_node_fs_watch(watchPath, options?.recursive || false);
+      while (true) {
+        promise = op_node_fs_watch_next(rid);
+        if (!refed) {
+          core.unrefOpPromise(promise);
+        }
+        const event = await promise;
+        if (event === null) {
+          return;
+        }
+        fsWatcher.emit("change", event.eventType, event.filename);
+      }
+    } catch (e) {
+      if (
+        e instanceof Deno.errors.BadResource ||
+        e instanceof Deno.errors.Interrupted
+      ) {
+        // the watcher was closed
+        return;
+      }
       fsWatcher.emit("error", e);
-    });
+    }
   }, 5);
 
   const fsWatcher = new FSWatcher(() => {
     clearTimeout(timer);
-    try {
-      iterator?.close();
-    } catch (e) {
-      if (e instanceof Deno.errors.BadResource) {
-        // already closed
-        return;
+    if (rid !== undefined) {
+      core.tryClose(rid);
+    }
+  }, (ref) => {
+    refed = ref;
+    if (promise) {
+      if (ref) {
+        core.refOpPromise(promise);
+      } else {
+        core.unrefOpPromise(promise);
       }
-      throw e;
     }
-  }, () => iterator);
+  });
 
   if (listener) {
     fsWatcher.on("change", listener.bind({ _handle: fsWatcher }));
@@ -317,12 +332,12 @@ class StatWatcher extends EventEmitter {
 class FSWatcher extends EventEmitter {
   #closer: () => void;
   #closed = false;
-  #watcher: () => Deno.FsWatcher;
+  #setRef: (ref: boolean) => void;
 
-  constructor(closer: () => void, getter: () => Deno.FsWatcher) {
+  constructor(closer: () => void, setRef: (ref: boolean) => void) {
     super();
     this.#closer = closer;
-    this.#watcher = getter;
+    this.#setRef = setRef;
   }
   close() {
     if (this.#closed) {
@@ -333,21 +348,14 @@ class FSWatcher extends EventEmitter {
     this.#closer();
   }
   ref() {
-    this.#watcher().ref();
+    this.#setRef(true);
   }
   unref() {
-    this.#watcher().unref();
+    this.#setRef(false);
   }
 }
 
-type NodeFsEventType = "rename" | "change";
-
-function convertDenoFsEventToNodeFsEvent(
-  kind: Deno.FsEvent["kind"],
-): NodeFsEventType {
-  if (kind === "create" || kind === "remove") {
-    return "rename";
-  } else {
-    return "change";
-  }
-}
+type NodeFsWatchEvent = {
+  eventType: "rename" | "change";
+  filename: string | null;
+};
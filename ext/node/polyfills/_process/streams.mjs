@@ -2,6 +2,10 @@
 // Copyright Joyent, Inc. and Node.js contributors. All rights reserved. MIT license.
 
 import { primordials } from "ext:core/mod.js";
+import {
+  op_node_tty_set_raw_mode,
+  op_node_tty_window_size,
+} from "ext:core/ops";
 const {
   Uint8ArrayPrototype,
   Error,
@@ -9,7 +13,6 @@ const {
   ObjectDefineProperty,
   TypedArrayPrototypeSlice,
   PromisePrototypeThen,
-  ObjectValues,
   ObjectPrototypeIsPrototypeOf,
 } = primordials;
 
@@ -68,12 +71,13 @@ export function createWritableStdioStream(writer, name, warmup = false) {
       enumerable: true,
       configurable: true,
       get: () =>
-        writer?.isTerminal() ? Deno.consoleSize?.().columns : undefined,
+        writer?.isTerminal() ? op_node_tty_window_size(fd)[0] : undefined,
     },
     rows: {
       enumerable: true,
       configurable: true,
-      get: () => writer?.isTerminal() ? Deno.consoleSize?.().rows : undefined,
+      get: () =>
+        writer?.isTerminal() ? op_node_tty_window_size(fd)[1] : undefined,
     },
     isTTY: {
       enumerable: true,
@@ -84,7 +88,7 @@ export function createWritableStdioStream(writer, name, warmup = false) {
       enumerable: true,
       configurable: true,
       value: () =>
-        writer?.isTerminal() ? ObjectValues(Deno.consoleSize?.()) : undefined,
+        writer?.isTerminal() ? op_node_tty_window_size(fd) : undefined,
     },
   });
 
@@ -211,7 +215,9 @@ export const initStdin = (warmup = false) => {
   });
   stdin._isRawMode = false;
   stdin.setRawMode = (enable) => {
-    io.stdin?.setRaw?.(enable);
+    if (io.stdin) {
+      op_node_tty_set_raw_mode(io.STDIN_RID, enable);
+    }
     stdin._isRawMode = enable;
     return stdin;
   };
@@ -2059,6 +2059,16 @@ export const constants: {
 };
 export const kMaxLength: number;
 export const kStringMaxLength: number;
+/**
+ * Re-encodes the given `Buffer` or `Uint8Array` instance from one character
+ * encoding to another. Returns a new `Buffer` instance.
+ * @since v7.1.0
+ */
+export function transcode(
+  source: Uint8Array,
+  fromEnc: Encoding,
+  toEnc: Encoding,
+): Buffer;
 
 declare const exports: {
   atob: typeof atob;
@@ -2069,6 +2079,7 @@ declare const exports: {
   kMaxLength: typeof kMaxLength;
   kStringMaxLength: typeof kStringMaxLength;
   SlowBuffer: typeof SlowBuffer;
+  transcode: typeof transcode;
 };
 
 export default exports;
@@ -6,7 +6,11 @@
 // deno-lint-ignore-file prefer-primordials
 
 import { core } from "ext:core/mod.js";
-import { op_is_ascii, op_is_utf8 } from "ext:core/ops";
+import {
+  op_is_ascii,
+  op_is_utf8,
+  op_node_buffer_transcode,
+} from "ext:core/ops";
 
 import { TextDecoder, TextEncoder } from "ext:deno_web/08_text_encoding.js";
 import { codes } from "ext:deno_node/internal/error_codes.ts";
@@ -2598,6 +2602,32 @@ export function isAscii(input) {
   ], input);
 }
 
+export function transcode(source, fromEncoding, toEncoding) {
+  if (!isUint8Array(source)) {
+    throw new codes.ERR_INVALID_ARG_TYPE(
+      "source",
+      ["Buffer", "Uint8Array"],
+      source,
+    );
+  }
+  if (source.length === 0) {
+    return Buffer.alloc(0);
+  }
+  const from = normalizeEncoding(fromEncoding) ?? `${fromEncoding}`;
+  const to = normalizeEncoding(toEncoding) ?? `${toEncoding}`;
+  let result;
+  try {
+    result = op_node_buffer_transcode(source, from, to);
+  } catch {
+    const err = new Error(
+      "Unable to transcode Buffer [U_ILLEGAL_ARGUMENT_ERROR]",
+    );
+    err.code = "U_ILLEGAL_ARGUMENT_ERROR";
+    throw err;
+  }
+  return Buffer.from(result.buffer, result.byteOffset, result.byteLength);
+}
+
 export default {
   atob,
   btoa,
@@ -2609,4 +2639,5 @@ export default {
   kMaxLength,
   kStringMaxLength,
   SlowBuffer,
+  transcode,
 };
@@ -7,7 +7,13 @@
 // deno-lint-ignore-file prefer-primordials
 
 import { core, internals } from "ext:core/mod.js";
-import { op_node_ipc_read, op_node_ipc_write } from "ext:core/ops";
+import {
+  op_node_ipc_read,
+  op_node_ipc_write,
+  op_node_spawn,
+  op_node_spawn_kill,
+  op_node_spawn_wait,
+} from "ext:core/ops";
 import {
   ArrayIsArray,
   ArrayPrototypeFilter,
@@ -46,6 +52,12 @@ import {
 import { kEmptyObject } from "ext:deno_node/internal/util.mjs";
 import { getValidatedPath } from "ext:deno_node/internal/fs/utils.mjs";
 import process from "node:process";
+import {
+  readableStreamForRidUnrefable,
+  readableStreamForRidUnrefableRef,
+  readableStreamForRidUnrefableUnref,
+  writableStreamForRid,
+} from "ext:deno_web/06_streams.js";
 
 export function mapValues<T, O>(
   record: Readonly<Record<string, T>>,
@@ -97,6 +109,105 @@ export function stdioStringToArray(
   return options;
 }
 
+interface NodeSpawnStatus {
+  code: number | null;
+  signal: number | null;
+}
+
+/**
+ * A child spawned with `op_node_spawn`, exposing the part of
+ * `Deno.ChildProcess` that `ChildProcess` uses. Unlike `Deno.Command` it
+ * accepts the parent's stdio fds.
+ */
+class NodeSpawnedChild {
+  pid: number;
+  stdin: WritableStream<Uint8Array> | null = null;
+  stdout: ReadableStream<Uint8Array> | null = null;
+  stderr: ReadableStream<Uint8Array> | null = null;
+  status: Promise<NodeSpawnStatus>;
+  #rid: number;
+  #waitPromise: Promise<NodeSpawnStatus>;
+  #exited = false;
+
+  constructor(config: {
+    command: string;
+    args: string[];
+    cwd?: string;
+    env: [string, string][] | null;
+    stdio: (string | number)[];
+  }) {
+    const { rid, pid, stdinRid, stdoutRid, stderrRid } = op_node_spawn(
+      config,
+    );
+    this.#rid = rid;
+    this.pid = pid;
+    if (stdinRid != null) {
+      this.stdin = writableStreamForRid(stdinRid);
+    }
+    if (stdoutRid != null) {
+      this.stdout = readableStreamForRidUnrefable(stdoutRid);
+    }
+    if (stderrRid != null) {
+      this.stderr = readableStreamForRidUnrefable(stderrRid);
+    }
+    this.#waitPromise = op_node_spawn_wait(rid);
+    this.status = this.#waitPromise.then((status) => {
+      this.#exited = true;
+      return status;
+    });
+  }
+
+  kill(signal: Deno.Signal) {
+    try {
+      if (this.#exited) {
+        throw new Deno.errors.BadResource();
+      }
+      op_node_spawn_kill(this.#rid, os.signals[signal]);
+    } catch (err) {
+      // the resource is closed as soon as the child has been waited on
+      if (err instanceof Deno.errors.BadResource) {
+        throw new TypeError("Child process has already terminated");
+      }
+      throw err;
+    }
+  }
+
+  ref() {
+    core.refOpPromise(this.#waitPromise);
+    if (this.stdout) readableStreamForRidUnrefableRef(this.stdout);
+    if (this.stderr) readableStreamForRidUnrefableRef(this.stderr);
+  }
+
+  unref() {
+    core.unrefOpPromise(this.#waitPromise);
+    if (this.stdout) readableStreamForRidUnrefableUnref(this.stdout);
+    if (this.stderr) readableStreamForRidUnrefableUnref(this.stderr);
+  }
+}
+
+function toNodeSpawnStdio(
+  pipe: NodeStdio | number | Stream | null | undefined,
+): string | number {
+  if (pipe instanceof Stream) {
+    return "inherit";
+  }
+  if (typeof pipe === "number") {
+    return pipe;
+  }
+  switch (pipe) {
+    case "pipe":
+    case "overlapped":
+    case undefined:
+    case null:
+      return "pipe";
+    case "ignore":
+    case "inherit":
+      return pipe;
+    default:
+      notImplemented(`toNodeSpawnStdio pipe=${typeof pipe} (${pipe})`);
+  }
+}
+
 export class ChildProcess extends EventEmitter {
   /**
    * The exit code of the child process. This property will be `null` until the child process exits.
@@ -152,7 +263,7 @@ export class ChildProcess extends EventEmitter {
     null,
   ];
 
-  #process!: Deno.ChildProcess;
+  #process!: Deno.ChildProcess | NodeSpawnedChild;
   #spawned = Promise.withResolvers<void>();
 
   constructor(
@@ -189,16 +300,28 @@ export class ChildProcess extends EventEmitter {
 
     const stringEnv = mapValues(env, (value) => value.toString());
     try {
-      this.#process = new Deno.Command(cmd, {
-        args: cmdArgs,
-        cwd,
-        env: stringEnv,
-        stdin: toDenoStdio(stdin),
-        stdout: toDenoStdio(stdout),
-        stderr: toDenoStdio(stderr),
-        windowsRawArguments: windowsVerbatimArguments,
-        ipc, // internal
-      }).spawn();
+      if (ipc === -1 && !windowsVerbatimArguments) {
+        this.#process = new NodeSpawnedChild({
+          command: cmd,
+          args: cmdArgs,
+          cwd,
+          // without an explicit env the child inherits the parent's
+          env: options?.env ? Object.entries(stringEnv) : null,
+          stdio: [stdin, stdout, stderr].map(toNodeSpawnStdio),
+        });
+      } else {
+        // the op doesn't set up an IPC channel or pass arguments verbatim
+        this.#process = new Deno.Command(cmd, {
+          args: cmdArgs,
+          cwd,
+          env: stringEnv,
+          stdin: toDenoStdio(stdin),
+          stdout: toDenoStdio(stdout),
+          stderr: toDenoStdio(stderr),
+          windowsRawArguments: windowsVerbatimArguments,
+          ipc, // internal
+        }).spawn();
+      }
       this.pid = this.#process.pid;
 
       if (stdin === "pipe") {
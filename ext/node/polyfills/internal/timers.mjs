@@ -20,9 +20,11 @@ import { ERR_OUT_OF_RANGE } from "ext:deno_node/internal/errors.ts";
 import { emitWarning } from "node:process";
 import {
   clearTimeout as clearTimeout_,
+  refTimer,
   setImmediate as setImmediate_,
   setInterval as setInterval_,
   setTimeout as setTimeout_,
+  unrefTimer,
 } from "ext:deno_web/02_timers.js";
 
 // Timeout values > TIMEOUT_MAX are set to 1.
@@ -67,7 +69,7 @@ Timeout.prototype[createTimer] = function () {
     ? setInterval_(cb, this._idleTimeout, ...this._timerArgs)
     : setTimeout_(cb, this._idleTimeout, ...this._timerArgs);
   if (!this[kRefed]) {
-    Deno.unrefTimer(id);
+    unrefTimer(id);
   }
   MapPrototypeSet(activeTimers, id, this);
   return id;
@@ -95,7 +97,7 @@ Timeout.prototype.refresh = function () {
 Timeout.prototype.unref = function () {
   if (this[kRefed]) {
     this[kRefed] = false;
-    Deno.unrefTimer(this[kTimerId]);
+    unrefTimer(this[kTimerId]);
   }
   return this;
 };
@@ -103,7 +105,7 @@ Timeout.prototype.unref = function () {
 Timeout.prototype.ref = function () {
   if (!this[kRefed]) {
     this[kRefed] = true;
-    Deno.refTimer(this[kTimerId]);
+    refTimer(this[kTimerId]);
   }
   return this;
 };
@@ -3,6 +3,10 @@
 // TODO(petamoriken): enable prefer-primordials for node polyfills
 // deno-lint-ignore-file prefer-primordials
 
+import {
+  op_node_event_loop_utilization,
+  op_node_perf_now,
+} from "ext:core/ops";
 import { notImplemented } from "ext:deno_node/_utils.ts";
 import {
   performance as shimPerformance,
@@ -20,6 +24,30 @@ class PerformanceObserver {
 
 const constants = {};
 
+interface EventLoopUtilization {
+  idle: number;
+  active: number;
+  utilization: number;
+}
+
+function eventLoopUtilization(
+  util1?: EventLoopUtilization,
+  util2?: EventLoopUtilization,
+): EventLoopUtilization {
+  if (util1 && util2) {
+    const idle = util1.idle - util2.idle;
+    const active = util1.active - util2.active;
+    return { idle, active, utilization: active / (idle + active) };
+  }
+  const current: EventLoopUtilization = op_node_event_loop_utilization();
+  if (!util1) {
+    return current;
+  }
+  const idle = current.idle - util1.idle;
+  const active = current.active - util1.active;
+  return { idle, active, utilization: active / (idle + active) };
+}
+
 const performance:
   & Omit<
     Performance,
@@ -36,30 +64,20 @@ const performance:
     // deno-lint-ignore no-explicit-any
     markResourceTiming: any;
   } = {
-    clearMarks: (markName: string) => shimPerformance.clearMarks(markName),
-    eventLoopUtilization: () =>
-      notImplemented("eventLoopUtilization from performance"),
-    mark: (markName: string) => shimPerformance.mark(markName),
+    clearMarks: (markName?: string) => shimPerformance.clearMarks(markName),
+    eventLoopUtilization,
+    // marks and measures share the web performance timeline, so marks made
+    // with `globalThis.performance` can be measured here too
+    mark: (markName: string, options?: PerformanceMarkOptions) =>
+      shimPerformance.mark(markName, options),
     measure: (
       measureName: string,
       startMark?: string | PerformanceMeasureOptions,
       endMark?: string,
-    ): PerformanceMeasure => {
-      if (endMark) {
-        return shimPerformance.measure(
-          measureName,
-          startMark as string,
-          endMark,
-        );
-      } else {
-        return shimPerformance.measure(
-          measureName,
-          startMark as PerformanceMeasureOptions,
-        );
-      }
-    },
+    ): PerformanceMeasure =>
+      shimPerformance.measure(measureName, startMark, endMark),
     nodeTiming: {},
-    now: () => shimPerformance.now(),
+    now: () => op_node_perf_now(),
     timerify: () => notImplemented("timerify from performance"),
     get timeOrigin() {
       // deno-lint-ignore no-explicit-any
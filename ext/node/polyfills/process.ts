@@ -8,6 +8,7 @@ import { core, internals } from "ext:core/mod.js";
 import { initializeDebugEnv } from "ext:deno_node/internal/util/debuglog.ts";
 import {
   op_geteuid,
+  op_node_process_config,
   op_node_process_kill,
   op_process_abort,
 } from "ext:core/ops";
@@ -402,12 +403,7 @@ Object.defineProperty(Process.prototype, "argv0", {
 Process.prototype.chdir = chdir;
 
 /** https://nodejs.org/api/process.html#processconfig */
-Process.prototype.config = {
-  target_defaults: {
-    default_configuration: "Release",
-  },
-  variables: {},
-};
+Process.prototype.config = op_node_process_config();
 
 /** https://nodejs.org/api/process.html#process_process_cwd */
 Process.prototype.cwd = cwd;
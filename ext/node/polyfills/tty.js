@@ -1,6 +1,10 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
 import { core, primordials } from "ext:core/mod.js";
+import {
+  op_node_tty_set_raw_mode,
+  op_node_tty_window_size,
+} from "ext:core/ops";
 const {
   Error,
 } = primordials;
@@ -72,7 +76,7 @@ export class ReadStream extends Socket {
 
   setRawMode(flag) {
     flag = !!flag;
-    this._handle.setRaw(flag);
+    op_node_tty_set_raw_mode(io.STDIN_RID, flag);
 
     this.isRaw = flag;
     return this;
@@ -100,11 +104,17 @@ export class WriteStream extends Socket {
       manualStart: true,
     });
 
-    const { columns, rows } = Deno.consoleSize();
+    this.fd = fd;
+    const [columns, rows] = this.getWindowSize();
     this.columns = columns;
     this.rows = rows;
     this.isTTY = true;
   }
+
+  getWindowSize() {
+    // rids 0, 1 and 2 are the stdio fds
+    return op_node_tty_window_size(this.fd);
+  }
 }
 
 export { isatty };
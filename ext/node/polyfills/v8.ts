@@ -9,24 +9,40 @@
 import { core } from "ext:core/mod.js";
 import {
   op_v8_cached_data_version_tag,
+  op_v8_get_heap_code_statistics,
+  op_v8_get_heap_snapshot,
+  op_v8_get_heap_space_statistics,
   op_v8_get_heap_statistics,
+  op_v8_write_heap_snapshot,
 } from "ext:core/ops";
 
 import { Buffer } from "node:buffer";
+import { Readable } from "node:stream";
+import { threadId } from "node:worker_threads";
 
 import { notImplemented } from "ext:deno_node/_utils.ts";
 
 export function cachedDataVersionTag() {
   return op_v8_cached_data_version_tag();
 }
+const codeBuffer = new Float64Array(4);
+
 export function getHeapCodeStatistics() {
-  notImplemented("v8.getHeapCodeStatistics");
+  codeBuffer.fill(0);
+  op_v8_get_heap_code_statistics(codeBuffer);
+
+  return {
+    code_and_metadata_size: codeBuffer[0],
+    bytecode_and_metadata_size: codeBuffer[1],
+    external_script_source_size: codeBuffer[2],
+    cpu_profiler_metadata_size: codeBuffer[3],
+  };
 }
 export function getHeapSnapshot() {
-  notImplemented("v8.getHeapSnapshot");
+  return Readable.from([Buffer.from(op_v8_get_heap_snapshot())]);
 }
 export function getHeapSpaceStatistics() {
-  notImplemented("v8.getHeapSpaceStatistics");
+  return op_v8_get_heap_space_statistics();
 }
 
 const buffer = new Float64Array(14);
@@ -68,8 +84,8 @@ export function stopCoverage() {
 export function takeCoverage() {
   notImplemented("v8.takeCoverage");
 }
-export function writeHeapSnapshot() {
-  notImplemented("v8.writeHeapSnapshot");
+export function writeHeapSnapshot(filename?: string) {
+  return op_v8_write_heap_snapshot(filename, threadId);
 }
 export function serialize(value) {
   return Buffer.from(core.serialize(value));
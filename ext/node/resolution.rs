@@ -2,9 +2,12 @@
 
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::path::Path;
 use std::path::PathBuf;
+use std::rc::Rc;
 
+use deno_config::package_json::PackageJsonLoadError;
 use deno_config::package_json::PackageJsonRc;
 use deno_core::anyhow::bail;
 use deno_core::error::generic_error;
@@ -15,11 +18,13 @@ use deno_core::url::Url;
 use deno_core::ModuleSpecifier;
 use deno_fs::FileSystemRc;
 use deno_media_type::MediaType;
+use serde::Serialize;
 
 use crate::errors;
-use crate::is_builtin_node_module;
 use crate::path::to_file_specifier;
 use crate::polyfill::get_module_name_from_builtin_node_module_specifier;
+use crate::BuiltinNodeModules;
+use crate::NodeModulePolyfill;
 use crate::NpmResolverRc;
 use crate::PackageJson;
 use crate::PathClean;
@@ -27,6 +32,41 @@ use crate::PathClean;
 pub static DEFAULT_CONDITIONS: &[&str] = &["deno", "node", "import"];
 pub static REQUIRE_CONDITIONS: &[&str] = &["require", "node"];
 
+/// The conditions used when resolving from a module of the given kind, so
+/// "import" is only active for ESM referrers and "require" for CJS ones.
+pub fn conditions_for_kind(kind: NodeModuleKind) -> &'static [&'static str] {
+  match kind {
+    NodeModuleKind::Esm => DEFAULT_CONDITIONS,
+    NodeModuleKind::Cjs => REQUIRE_CONDITIONS,
+  }
+}
+
+/// Maps the value of `NODE_ENV` to the condition libraries use to select
+/// their build for it. Other values don't activate any condition.
+pub fn node_env_condition(node_env: &str) -> Option<&'static str> {
+  match node_env {
+    "production" => Some("production"),
+    "development" => Some("development"),
+    _ => None,
+  }
+}
+
+/// Conditions with fixed semantics that can't be provided as custom
+/// conditions.
+static RESERVED_CONDITIONS: &[&str] = &[
+  "default",
+  "deno",
+  "node",
+  "node-addons",
+  "import",
+  "require",
+  "types",
+];
+
+/// How many arrays and condition objects may be nested within a single
+/// "exports" or "imports" target.
+const MAX_PACKAGE_TARGET_DEPTH: usize = 32;
+
 pub type NodeModuleKind = deno_config::package_json::NodeModuleKind;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -100,6 +140,21 @@ impl NodeResolution {
   }
 }
 
+/// A warning about deprecated behavior that resolution relied on, which
+/// the node extension emits with `process.emitWarning()`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NodeResolutionWarning {
+  pub code: &'static str,
+  pub message: String,
+}
+
+#[derive(Debug, Default)]
+struct ResolutionWarnings {
+  pending: Vec<NodeResolutionWarning>,
+  /// Like in Node, each warning is only emitted once.
+  emitted: HashSet<String>,
+}
+
 #[allow(clippy::disallowed_types)]
 pub type NodeResolverRc = deno_fs::sync::MaybeArc<NodeResolver>;
 
@@ -108,6 +163,17 @@ pub struct NodeResolver {
   fs: FileSystemRc,
   npm_resolver: NpmResolverRc,
   in_npm_package_cache: deno_fs::sync::MaybeArcMutex<HashMap<String, bool>>,
+  custom_conditions: Vec<String>,
+  node_addons: bool,
+  folder_exports: bool,
+  browser_field: bool,
+  module_field: bool,
+  lenient_exports_keys: bool,
+  tolerant_package_json: bool,
+  allowed_conditions: Option<HashSet<String>>,
+  node_env_condition: Option<&'static str>,
+  builtin_node_modules: BuiltinNodeModules,
+  warnings: deno_fs::sync::MaybeArcMutex<ResolutionWarnings>,
 }
 
 impl NodeResolver {
@@ -116,6 +182,157 @@ impl NodeResolver {
       fs,
       npm_resolver,
       in_npm_package_cache: deno_fs::sync::MaybeArcMutex::new(HashMap::new()),
+      custom_conditions: Vec::new(),
+      node_addons: true,
+      folder_exports: false,
+      browser_field: false,
+      module_field: false,
+      lenient_exports_keys: false,
+      tolerant_package_json: false,
+      allowed_conditions: None,
+      node_env_condition: None,
+      builtin_node_modules: BuiltinNodeModules::default(),
+      warnings: deno_fs::sync::MaybeArcMutex::new(Default::default()),
+    }
+  }
+
+  /// Registers builtin modules provided by the embedder in addition to (or
+  /// in place of) the polyfills shipped with this crate. The node extension
+  /// exposes the same set to `require()`.
+  pub fn with_extra_polyfills(
+    mut self,
+    extra_polyfills: Vec<NodeModulePolyfill>,
+  ) -> Self {
+    self.builtin_node_modules.extend(extra_polyfills);
+    self
+  }
+
+  pub fn builtin_node_modules(&self) -> &BuiltinNodeModules {
+    &self.builtin_node_modules
+  }
+
+  /// Sets whether the legacy "browser" field of a package.json is consulted
+  /// when the "browser" condition is active. "exports" still takes
+  /// precedence when it has a "browser" condition. Disabled by default.
+  pub fn with_browser_field(mut self, enabled: bool) -> Self {
+    self.browser_field = enabled;
+    self
+  }
+
+  /// Sets whether the non-standard "module" field of a package.json, which
+  /// bundlers use for an ESM entry point, is preferred over "main" when
+  /// importing a package without "exports". Node ignores this field, so
+  /// this is disabled by default.
+  pub fn with_module_field(mut self, enabled: bool) -> Self {
+    self.module_field = enabled;
+    self
+  }
+
+  /// Sets whether deprecated folder mappings in "exports" (ex.
+  /// `"./dir/": "./src/dir/"`) are resolved. A DEP0148 warning is raised
+  /// the first time each one is used. This is disabled by default because
+  /// Node removed support for them in favour of subpath patterns.
+  pub fn with_folder_exports(mut self, enabled: bool) -> Self {
+    self.folder_exports = enabled;
+    self
+  }
+
+  /// Sets whether subpath keys in "exports" that are missing the leading
+  /// "./" (ex. `"sub": "./sub.js"` next to `".": "./index.js"`) are treated
+  /// as if they had it, like some bundlers do. Node ignores these keys, so
+  /// this is disabled by default.
+  pub fn with_lenient_exports_keys(mut self, enabled: bool) -> Self {
+    self.lenient_exports_keys = enabled;
+    self
+  }
+
+  /// Sets whether package.json files may contain comments and trailing
+  /// commas, as emitted by some monorepo tooling. Node rejects these files,
+  /// so this is disabled by default.
+  pub fn with_tolerant_package_json(mut self, enabled: bool) -> Self {
+    self.tolerant_package_json = enabled;
+    self
+  }
+
+  /// Restricts the condition keys of "exports" and "imports" that may be
+  /// matched. A branch keyed by a condition outside of the allowlist is
+  /// ignored even when the condition is active, so resolution falls through
+  /// to the next key (usually "default", which is always allowed). `None`
+  /// (the default) allows every condition.
+  pub fn with_allowed_conditions(
+    mut self,
+    allowed_conditions: Option<HashSet<String>>,
+  ) -> Self {
+    self.allowed_conditions = allowed_conditions;
+    self
+  }
+
+  /// Sets the value of `NODE_ENV`, which activates the "production" or
+  /// "development" condition when it's set to one of those.
+  pub fn with_node_env(mut self, node_env: Option<&str>) -> Self {
+    self.node_env_condition = node_env.and_then(node_env_condition);
+    self
+  }
+
+  /// Sets whether the "node-addons" condition is matched when resolving
+  /// "exports" and "imports" for CommonJS. This is enabled by default and
+  /// should be disabled when native addons can't be loaded.
+  pub fn with_node_addons(mut self, enabled: bool) -> Self {
+    self.node_addons = enabled;
+    self
+  }
+
+  /// Adds user supplied conditions that are matched in addition to the
+  /// default ones when resolving "exports" and "imports" (similar to
+  /// Node's `--conditions` flag).
+  ///
+  /// Errors when a reserved condition such as "default" is provided because
+  /// its semantics can't be overridden.
+  pub fn with_custom_conditions(
+    mut self,
+    conditions: Vec<String>,
+  ) -> Result<Self, AnyError> {
+    if let Some(condition) = conditions
+      .iter()
+      .find(|c| RESERVED_CONDITIONS.contains(&c.as_str()))
+    {
+      return Err(generic_error(format!(
+        "Invalid custom condition \"{condition}\". The \"{condition}\" condition is reserved and can't be overridden."
+      )));
+    }
+    self.custom_conditions = conditions;
+    Ok(self)
+  }
+
+  /// Extends the conditions provided by a caller with the ones configured
+  /// on this resolver: the custom conditions, the one for `NODE_ENV` and
+  /// "node-addons" for CommonJS referrers.
+  fn with_implicit_conditions<'a>(
+    &'a self,
+    conditions: &[&'a str],
+    referrer_kind: NodeModuleKind,
+  ) -> Vec<&'a str> {
+    let mut conditions = conditions.to_vec();
+    conditions.extend(self.custom_conditions.iter().map(|c| c.as_str()));
+    conditions.extend(self.node_env_condition);
+    if self.node_addons && matches!(referrer_kind, NodeModuleKind::Cjs) {
+      conditions.push("node-addons");
+    }
+    conditions
+  }
+
+  /// Takes the warnings raised since the last call, which the caller is
+  /// expected to surface to the user.
+  pub fn take_warnings(&self) -> Vec<NodeResolutionWarning> {
+    std::mem::take(&mut self.warnings.lock().pending)
+  }
+
+  fn warn(&self, code: &'static str, message: String) {
+    let mut warnings = self.warnings.lock();
+    if warnings.emitted.insert(message.clone()) {
+      warnings
+        .pending
+        .push(NodeResolutionWarning { code, message });
     }
   }
 
@@ -151,7 +368,7 @@ impl NodeResolver {
     // Note: if we are here, then the referrer is an esm module
     // TODO(bartlomieju): skipped "policy" part as we don't plan to support it
 
-    if crate::is_builtin_node_module(specifier) {
+    if self.builtin_node_modules.is_builtin(specifier) {
       return Ok(Some(NodeResolution::BuiltIn(specifier.to_string())));
     }
 
@@ -179,8 +396,9 @@ impl NodeResolver {
       }
     }
 
-    let url =
-      self.module_resolve(specifier, referrer, DEFAULT_CONDITIONS, mode)?;
+    let conditions =
+      self.with_implicit_conditions(DEFAULT_CONDITIONS, NodeModuleKind::Esm);
+    let url = self.module_resolve(specifier, referrer, &conditions, mode)?;
     let url = match url {
       Some(url) => url,
       None => return Ok(None),
@@ -230,7 +448,7 @@ impl NodeResolver {
       }
     } else if specifier.starts_with('#') {
       let pkg_config = self.get_closest_package_json(referrer)?;
-      Some(self.package_imports_resolve(
+      Some(self.package_imports_resolve_exact(
         specifier,
         referrer,
         NodeModuleKind::Esm,
@@ -241,7 +459,7 @@ impl NodeResolver {
     } else if let Ok(resolved) = Url::parse(specifier) {
       Some(resolved)
     } else {
-      self.package_resolve(
+      self.package_resolve_exact(
         specifier,
         referrer,
         NodeModuleKind::Esm,
@@ -283,7 +501,7 @@ impl NodeResolver {
 
     let p_str = path.to_str().unwrap();
     let p = if p_str.ends_with('/') {
-      p_str[p_str.len() - 1..].to_string()
+      p_str[..p_str.len() - 1].to_string()
     } else {
       p_str.to_string()
     };
@@ -321,12 +539,14 @@ impl NodeResolver {
     let package_subpath = package_subpath
       .map(|s| format!("./{s}"))
       .unwrap_or_else(|| ".".to_string());
+    let conditions =
+      self.with_implicit_conditions(DEFAULT_CONDITIONS, node_module_kind);
     let maybe_resolved_url = self.resolve_package_dir_subpath(
       package_dir,
       &package_subpath,
       referrer,
       node_module_kind,
-      DEFAULT_CONDITIONS,
+      &conditions,
       mode,
     )?;
     let resolved_url = match maybe_resolved_url {
@@ -417,6 +637,9 @@ impl NodeResolver {
       }
     } else if url_str.ends_with(".mjs") || url_str.ends_with(".d.mts") {
       Ok(NodeResolution::Esm(url))
+    } else if url_str.ends_with(".cjs") || url_str.ends_with(".d.cts") {
+      // explicitly CommonJS regardless of the package's "type"
+      Ok(NodeResolution::CommonJs(url))
     } else if url_str.ends_with(".ts") || url_str.ends_with(".mts") {
       if self.in_npm_package(&url) {
         Err(generic_error(format!(
@@ -430,6 +653,32 @@ impl NodeResolver {
     }
   }
 
+  /// Errors with `ERR_REQUIRE_ESM` when the file is an ES module, which is
+  /// the case for `.mjs` files and `.js` files in a package whose "type" is
+  /// "module". Other files are loaded as CommonJS by `require()`.
+  pub fn ensure_requireable(
+    &self,
+    file_path: &Path,
+    maybe_parent: Option<&str>,
+  ) -> Result<(), AnyError> {
+    let is_esm = match file_path.extension().and_then(|ext| ext.to_str()) {
+      Some("mjs") => true,
+      Some("js") => self
+        .get_closest_package_json_from_path(file_path)?
+        .map(|pkg_json| pkg_json.typ == "module")
+        .unwrap_or(false),
+      _ => false,
+    };
+    if is_esm {
+      Err(errors::err_require_esm(
+        &file_path.to_string_lossy(),
+        maybe_parent,
+      ))
+    } else {
+      Ok(())
+    }
+  }
+
   /// Checks if the resolved file has a corresponding declaration file.
   fn path_to_declaration_url(
     &self,
@@ -499,10 +748,10 @@ impl NodeResolver {
         /* sub path */ ".",
         referrer,
         referrer_kind,
-        match referrer_kind {
-          NodeModuleKind::Esm => DEFAULT_CONDITIONS,
-          NodeModuleKind::Cjs => REQUIRE_CONDITIONS,
-        },
+        &self.with_implicit_conditions(
+          conditions_for_kind(referrer_kind),
+          referrer_kind,
+        ),
         NodeResolutionMode::Types,
       )?;
       if let Some(resolution) = maybe_resolution {
@@ -534,6 +783,29 @@ impl NodeResolver {
     referrer_pkg_json: Option<&PackageJson>,
     conditions: &[&str],
     mode: NodeResolutionMode,
+  ) -> Result<ModuleSpecifier, AnyError> {
+    self.package_imports_resolve_exact(
+      name,
+      referrer,
+      referrer_kind,
+      referrer_pkg_json,
+      &self.with_implicit_conditions(conditions, referrer_kind),
+      mode,
+    )
+  }
+
+  /// Like `package_imports_resolve`, but only matches `conditions`, which
+  /// is used when a caller supplies a complete condition set rather than
+  /// extending the default one.
+  #[allow(clippy::too_many_arguments)]
+  pub(super) fn package_imports_resolve_exact(
+    &self,
+    name: &str,
+    referrer: &ModuleSpecifier,
+    referrer_kind: NodeModuleKind,
+    referrer_pkg_json: Option<&PackageJson>,
+    conditions: &[&str],
+    mode: NodeResolutionMode,
   ) -> Result<ModuleSpecifier, AnyError> {
     if name == "#" || name.starts_with("#/") || name.ends_with('/') {
       let reason = "is not a valid internal imports specifier name";
@@ -641,6 +913,17 @@ impl NodeResolver {
         referrer,
       ));
     }
+    // targets are URLs relative to the package, so a backslash is never a
+    // separator and would only resolve by accident on Windows
+    if target.contains('\\') {
+      return Err(throw_invalid_package_target(
+        match_,
+        target,
+        package_json_path,
+        internal,
+        referrer,
+      ));
+    }
     let invalid_segment_re =
       lazy_regex::regex!(r"(^|\\|/)(\.\.?|node_modules)(\\|/|$)");
     let pattern_re = lazy_regex::regex!(r"\*");
@@ -664,7 +947,7 @@ impl NodeResolver {
               format!("{target}{subpath}")
             };
             let package_json_url = to_file_specifier(package_json_path);
-            let result = match self.package_resolve(
+            let result = match self.package_resolve_exact(
               &export_target,
               &package_json_url,
               referrer_kind,
@@ -679,7 +962,7 @@ impl NodeResolver {
             return match result {
               Ok(url) => Ok(url),
               Err(err) => {
-                if is_builtin_node_module(target) {
+                if self.builtin_node_modules.is_builtin(target) {
                   Ok(
                     ModuleSpecifier::parse(&format!("node:{}", target))
                       .unwrap(),
@@ -711,6 +994,9 @@ impl NodeResolver {
     }
     let package_path = package_json_path.parent().unwrap();
     let resolved_path = package_path.join(target).clean();
+    // Containment is checked on the logical path like the spec does, so a
+    // target going through a symlinked subdirectory that physically lives
+    // outside the package is still allowed.
     if !resolved_path.starts_with(package_path) {
       return Err(throw_invalid_package_target(
         match_,
@@ -759,6 +1045,49 @@ impl NodeResolver {
     conditions: &[&str],
     mode: NodeResolutionMode,
   ) -> Result<Option<ModuleSpecifier>, AnyError> {
+    self.resolve_nested_package_target(
+      package_json_path,
+      target,
+      subpath,
+      package_subpath,
+      referrer,
+      referrer_kind,
+      pattern,
+      internal,
+      conditions,
+      mode,
+      0,
+    )
+  }
+
+  /// Resolves a target that is `depth` arrays or condition objects deep in
+  /// "exports" or "imports". The depth is bounded so a pathologically nested
+  /// package.json errors instead of overflowing the stack.
+  #[allow(clippy::too_many_arguments)]
+  fn resolve_nested_package_target(
+    &self,
+    package_json_path: &Path,
+    target: &Value,
+    subpath: &str,
+    package_subpath: &str,
+    referrer: &ModuleSpecifier,
+    referrer_kind: NodeModuleKind,
+    pattern: bool,
+    internal: bool,
+    conditions: &[&str],
+    mode: NodeResolutionMode,
+    depth: usize,
+  ) -> Result<Option<ModuleSpecifier>, AnyError> {
+    if depth > MAX_PACKAGE_TARGET_DEPTH {
+      let ie = if internal { "imports" } else { "exports" };
+      return Err(errors::err_invalid_package_config(
+        &package_json_path.display().to_string(),
+        Some(to_specifier_display_string(referrer)),
+        Some(format!(
+          "\"{ie}\" target for '{package_subpath}' is nested more than {MAX_PACKAGE_TARGET_DEPTH} levels deep"
+        )),
+      ));
+    }
     if let Some(target) = target.as_str() {
       let url = self.resolve_package_target_string(
         target,
@@ -785,7 +1114,7 @@ impl NodeResolver {
 
       let mut last_error = None;
       for target_item in target_arr {
-        let resolved_result = self.resolve_package_target(
+        let resolved_result = self.resolve_nested_package_target(
           package_json_path,
           target_item,
           subpath,
@@ -796,6 +1125,7 @@ impl NodeResolver {
           internal,
           conditions,
           mode,
+          depth + 1,
         );
 
         match resolved_result {
@@ -805,9 +1135,11 @@ impl NodeResolver {
             continue;
           }
           Err(e) => {
-            let err_string = e.to_string();
+            let is_invalid_target = e
+              .downcast_ref::<errors::InvalidPackageTargetError>()
+              .is_some();
             last_error = Some(e);
-            if err_string.starts_with("[ERR_INVALID_PACKAGE_TARGET]") {
+            if is_invalid_target {
               continue;
             }
             return Err(last_error.unwrap());
@@ -819,6 +1151,18 @@ impl NodeResolver {
       }
       return Err(last_error.unwrap());
     } else if let Some(target_obj) = target.as_object() {
+      // subpaths are only valid at the top level of "exports", so a subpath
+      // key here is nested below a condition or another subpath
+      if let Some(key) = target_obj.keys().find(|key| key.starts_with('.')) {
+        let ie = if internal { "imports" } else { "exports" };
+        return Err(errors::err_invalid_package_config(
+          &package_json_path.display().to_string(),
+          Some(to_specifier_display_string(referrer)),
+          Some(format!(
+            "\"{ie}\" cannot contain the subpath key \"{key}\" inside of a condition."
+          )),
+        ));
+      }
       for key in target_obj.keys() {
         // TODO(bartlomieju): verify that keys are not numeric
         // return Err(errors::err_invalid_package_config(
@@ -827,13 +1171,22 @@ impl NodeResolver {
         //   Some("\"exports\" cannot contain numeric property keys.".to_string()),
         // ));
 
+        if key != "default"
+          && self
+            .allowed_conditions
+            .as_ref()
+            .is_some_and(|allowed| !allowed.contains(key))
+        {
+          continue;
+        }
+
         if key == "default"
           || conditions.contains(&key.as_str())
           || mode.is_types() && key.as_str() == "types"
         {
           let condition_target = target_obj.get(key).unwrap();
 
-          let resolved = self.resolve_package_target(
+          let resolved = self.resolve_nested_package_target(
             package_json_path,
             condition_target,
             subpath,
@@ -844,6 +1197,7 @@ impl NodeResolver {
             internal,
             conditions,
             mode,
+            depth + 1,
           )?;
           match resolved {
             Some(resolved) => return Ok(Some(resolved)),
@@ -876,6 +1230,75 @@ impl NodeResolver {
     referrer_kind: NodeModuleKind,
     conditions: &[&str],
     mode: NodeResolutionMode,
+  ) -> Result<ModuleSpecifier, AnyError> {
+    self.package_exports_resolve_exact(
+      package_json_path,
+      package_subpath,
+      package_exports,
+      referrer,
+      referrer_kind,
+      &self.with_implicit_conditions(conditions, referrer_kind),
+      mode,
+    )
+  }
+
+  /// Like `package_exports_resolve`, but only matches `conditions`, which
+  /// is used when a caller supplies a complete condition set rather than
+  /// extending the default one.
+  #[allow(clippy::too_many_arguments)]
+  pub(crate) fn package_exports_resolve_exact(
+    &self,
+    package_json_path: &Path,
+    package_subpath: &str,
+    package_exports: &Map<String, Value>,
+    referrer: &ModuleSpecifier,
+    referrer_kind: NodeModuleKind,
+    conditions: &[&str],
+    mode: NodeResolutionMode,
+  ) -> Result<ModuleSpecifier, AnyError> {
+    let package_exports = if self.lenient_exports_keys {
+      normalize_exports_keys(package_exports)
+    } else {
+      Cow::Borrowed(package_exports)
+    };
+    let resolved = self.package_exports_resolve_inner(
+      package_json_path,
+      package_subpath,
+      &package_exports,
+      referrer,
+      referrer_kind,
+      conditions,
+      mode,
+    )?;
+    // once a package has "exports" a directory target is never probed
+    // for an index file
+    if !mode.is_types()
+      && resolved.scheme() == "file"
+      && self.fs.is_dir_sync(&to_file_path(&resolved))
+    {
+      return Err(match referrer_kind {
+        NodeModuleKind::Esm => errors::err_unsupported_dir_import(
+          resolved.as_str(),
+          referrer.as_str(),
+        ),
+        NodeModuleKind::Cjs => errors::err_cjs_module_not_found(
+          &to_file_path(&resolved).to_string_lossy(),
+        ),
+      });
+    }
+    Ok(resolved)
+  }
+
+  #[allow(clippy::too_many_arguments)]
+  fn package_exports_resolve_inner(
+    &self,
+    package_json_path: &Path,
+    package_subpath: &str,
+    package_exports: &Map<String, Value>,
+    referrer: &ModuleSpecifier,
+    referrer_kind: NodeModuleKind,
+    conditions: &[&str],
+    mode: NodeResolutionMode,
   ) -> Result<ModuleSpecifier, AnyError> {
     if package_exports.contains_key(package_subpath)
       && package_subpath.find('*').is_none()
@@ -965,6 +1388,45 @@ impl NodeResolver {
       }
     }
 
+    if self.folder_exports {
+      let folder_match = package_exports
+        .keys()
+        .filter(|key| {
+          key.ends_with('/')
+            && !key.contains('*')
+            && package_subpath.len() > key.len()
+            && package_subpath.starts_with(key.as_str())
+        })
+        .max_by_key(|key| key.len());
+      if let Some(key) = folder_match {
+        self.warn(
+          "DEP0148",
+          format!(
+            "Use of deprecated folder mapping \"{}\" in the \"exports\" field module resolution of the package at {}. Update this package.json to use a subpath pattern like \"{}*\".",
+            key,
+            package_json_path.display(),
+            key,
+          ),
+        );
+        let target = package_exports.get(key).unwrap();
+        let maybe_resolved = self.resolve_package_target(
+          package_json_path,
+          target,
+          &package_subpath[key.len()..],
+          key,
+          referrer,
+          referrer_kind,
+          false,
+          false,
+          conditions,
+          mode,
+        )?;
+        if let Some(resolved) = maybe_resolved {
+          return Ok(resolved);
+        }
+      }
+    }
+
     Err(throw_exports_not_found(
       package_subpath,
       package_json_path,
@@ -980,6 +1442,26 @@ impl NodeResolver {
     referrer_kind: NodeModuleKind,
     conditions: &[&str],
     mode: NodeResolutionMode,
+  ) -> Result<Option<ModuleSpecifier>, AnyError> {
+    self.package_resolve_exact(
+      specifier,
+      referrer,
+      referrer_kind,
+      &self.with_implicit_conditions(conditions, referrer_kind),
+      mode,
+    )
+  }
+
+  /// Like `package_resolve`, but only matches `conditions`, which is used
+  /// when a caller supplies a complete condition set rather than extending
+  /// the default one.
+  pub(super) fn package_resolve_exact(
+    &self,
+    specifier: &str,
+    referrer: &ModuleSpecifier,
+    referrer_kind: NodeModuleKind,
+    conditions: &[&str],
+    mode: NodeResolutionMode,
   ) -> Result<Option<ModuleSpecifier>, AnyError> {
     let (package_name, package_subpath, _is_scoped) =
       parse_npm_pkg_name(specifier, referrer)?;
@@ -991,7 +1473,7 @@ impl NodeResolver {
     if package_config.name.as_ref() == Some(&package_name) {
       if let Some(exports) = &package_config.exports {
         return self
-          .package_exports_resolve(
+          .package_exports_resolve_exact(
             &package_config.path,
             &package_subpath,
             exports,
@@ -1127,8 +1609,22 @@ impl NodeResolver {
     conditions: &[&str],
     mode: NodeResolutionMode,
   ) -> Result<Option<ModuleSpecifier>, AnyError> {
+    if self.browser_field
+      && conditions.contains(&"browser")
+      && !package_json
+        .exports
+        .as_ref()
+        .is_some_and(|exports| exports_have_condition(exports, "browser"))
+    {
+      if let Some(resolved) =
+        self.resolve_browser_field(package_json, package_subpath)?
+      {
+        return Ok(Some(resolved));
+      }
+    }
+
     if let Some(exports) = &package_json.exports {
-      let result = self.package_exports_resolve(
+      let result = self.package_exports_resolve_exact(
         &package_json.path,
         package_subpath,
         exports,
@@ -1154,6 +1650,11 @@ impl NodeResolver {
     }
 
     if package_subpath == "." {
+      if matches!(referrer_kind, NodeModuleKind::Esm) && !mode.is_types() {
+        if let Some(resolved) = self.esm_entry(package_json)? {
+          return Ok(Some(resolved));
+        }
+      }
       return self.legacy_main_resolve(
         package_json,
         referrer,
@@ -1171,6 +1672,81 @@ impl NodeResolver {
     )
   }
 
+  /// Resolves a subpath using the legacy "browser" field, which is either
+  /// a replacement for "main" or a map of files to their browser versions.
+  fn resolve_browser_field(
+    &self,
+    package_json: &PackageJson,
+    package_subpath: &str,
+  ) -> Result<Option<ModuleSpecifier>, AnyError> {
+    let Some(raw_package_json) =
+      self.load_package_json_value(&package_json.path)?
+    else {
+      return Ok(None);
+    };
+    let package_dir = package_json.path.parent().unwrap();
+    let target = match raw_package_json.get("browser") {
+      Some(Value::String(target)) if package_subpath == "." => {
+        Some(target.as_str())
+      }
+      Some(Value::Object(map)) => {
+        let request = if package_subpath == "." {
+          package_json
+            .main(NodeModuleKind::Cjs)
+            .unwrap_or("./index.js")
+        } else {
+          package_subpath
+        };
+        let request = request.strip_prefix("./").unwrap_or(request);
+        let with_js_ext = format!("{request}.js");
+        map
+          .iter()
+          .find(|(key, _)| {
+            let key = key.strip_prefix("./").unwrap_or(key);
+            key == request || key == with_js_ext
+          })
+          // a value of `false` means the module is ignored in the browser,
+          // which isn't supported
+          .and_then(|(_, target)| target.as_str())
+      }
+      _ => None,
+    };
+    Ok(
+      target.map(|target| to_file_specifier(&package_dir.join(target).clean())),
+    )
+  }
+
+  /// Returns the file named by the "module" field of the package.json when
+  /// it's enabled with `with_module_field` and the file exists.
+  pub fn esm_entry(
+    &self,
+    package_json: &PackageJson,
+  ) -> Result<Option<ModuleSpecifier>, AnyError> {
+    if !self.module_field {
+      return Ok(None);
+    }
+    let Some(raw_package_json) =
+      self.load_package_json_value(&package_json.path)?
+    else {
+      return Ok(None);
+    };
+    let Some(module) = raw_package_json
+      .get("module")
+      .and_then(Value::as_str)
+      .map(str::trim)
+      .filter(|module| !module.is_empty())
+    else {
+      return Ok(None);
+    };
+    let path = package_json.path.parent().unwrap().join(module).clean();
+    Ok(
+      self
+        .fs
+        .is_file_sync(&path)
+        .then(|| to_file_specifier(&path)),
+    )
+  }
+
   fn resolve_subpath_exact(
     &self,
     directory: &Path,
@@ -1223,9 +1799,11 @@ impl NodeResolver {
     &self,
     file_path: &Path,
   ) -> Result<Option<PackageJsonRc>, AnyError> {
-    let current_dir = deno_core::strip_unc_prefix(
-      self.fs.realpath_sync(file_path.parent().unwrap())?,
-    );
+    let Some(parent_dir) = file_path.parent() else {
+      return Ok(None);
+    };
+    let current_dir =
+      deno_core::strip_unc_prefix(self.fs.realpath_sync(parent_dir)?);
     let mut current_dir = current_dir.as_path();
     let package_json_path = current_dir.join("package.json");
     if let Some(pkg_json) = self.load_package_json(&package_json_path)? {
@@ -1242,14 +1820,67 @@ impl NodeResolver {
     Ok(None)
   }
 
+  /// Gets the path of the closest package.json without loading it.
+  pub fn get_closest_package_json_path(
+    &self,
+    file_path: &Path,
+  ) -> Result<Option<PathBuf>, AnyError> {
+    let Some(parent_dir) = file_path.parent() else {
+      return Ok(None);
+    };
+    let current_dir =
+      deno_core::strip_unc_prefix(self.fs.realpath_sync(parent_dir)?);
+    for dir in current_dir.ancestors() {
+      let package_json_path = dir.join("package.json");
+      if self.fs.is_file_sync(&package_json_path) {
+        return Ok(Some(package_json_path));
+      }
+    }
+    Ok(None)
+  }
+
+  /// Like `get_closest_package_json_path`, but for the tsconfig.json of
+  /// the TypeScript project `file_path` belongs to.
+  pub fn get_closest_tsconfig_path(
+    &self,
+    file_path: &Path,
+  ) -> Result<Option<PathBuf>, AnyError> {
+    let Some(parent_dir) = file_path.parent() else {
+      return Ok(None);
+    };
+    let current_dir =
+      deno_core::strip_unc_prefix(self.fs.realpath_sync(parent_dir)?);
+    for dir in current_dir.ancestors() {
+      let tsconfig_path = dir.join("tsconfig.json");
+      if self.fs.is_file_sync(&tsconfig_path) {
+        return Ok(Some(tsconfig_path));
+      }
+    }
+    Ok(None)
+  }
+
+  pub(super) fn load_package_json_value(
+    &self,
+    package_json_path: &Path,
+  ) -> Result<Option<Rc<Value>>, AnyError> {
+    crate::package_json::load_pkg_json_value(
+      &*self.fs,
+      package_json_path,
+      self.tolerant_package_json,
+    )
+    .map_err(package_json_load_error)
+  }
+
   pub(super) fn load_package_json(
     &self,
     package_json_path: &Path,
-  ) -> Result<
-    Option<PackageJsonRc>,
-    deno_config::package_json::PackageJsonLoadError,
-  > {
-    crate::package_json::load_pkg_json(&*self.fs, package_json_path)
+  ) -> Result<Option<PackageJsonRc>, AnyError> {
+    let result = if self.tolerant_package_json {
+      crate::package_json::load_pkg_json_tolerant(&*self.fs, package_json_path)
+    } else {
+      crate::package_json::load_pkg_json(&*self.fs, package_json_path)
+    };
+    result.map_err(package_json_load_error)
   }
 
   pub(super) fn legacy_main_resolve(
@@ -1352,7 +1983,53 @@ impl NodeResolver {
   }
 }
 
-fn resolve_bin_entry_value<'a>(
+/// Adds the missing "./" to subpath keys of an "exports" map. Keys of a
+/// map without any "." key are conditions and are left alone, as is a key
+/// that would collide with an existing one.
+fn normalize_exports_keys(
+  exports: &Map<String, Value>,
+) -> Cow<Map<String, Value>> {
+  let is_subpath_map = exports.keys().any(|key| key.starts_with('.'));
+  if !is_subpath_map || exports.keys().all(|key| key.starts_with('.')) {
+    return Cow::Borrowed(exports);
+  }
+  let mut normalized = Map::with_capacity(exports.len());
+  for (key, value) in exports {
+    if key.starts_with('.') {
+      normalized.insert(key.clone(), value.clone());
+    }
+  }
+  for (key, value) in exports {
+    if !key.starts_with('.') {
+      normalized
+        .entry(format!("./{key}"))
+        .or_insert_with(|| value.clone());
+    }
+  }
+  Cow::Owned(normalized)
+}
+
+/// Checks if the exports object or any nested conditional object has the
+/// provided condition.
+fn exports_have_condition(
+  exports: &Map<String, Value>,
+  condition: &str,
+) -> bool {
+  exports.iter().any(|(key, value)| {
+    key == condition
+      || match value {
+        Value::Object(map) => exports_have_condition(map, condition),
+        Value::Array(items) => items.iter().any(|item| {
+          item
+            .as_object()
+            .is_some_and(|map| exports_have_condition(map, condition))
+        }),
+        _ => false,
+      }
+  })
+}
+
+pub(crate) fn resolve_bin_entry_value<'a>(
   package_json: &'a PackageJson,
   bin_name: Option<&str>,
 ) -> Result<&'a str, AnyError> {
@@ -1445,7 +2122,9 @@ fn to_file_path_string(url: &ModuleSpecifier) -> String {
   to_file_path(url).display().to_string()
 }
 
-fn should_be_treated_as_relative_or_absolute_path(specifier: &str) -> bool {
+pub(crate) fn should_be_treated_as_relative_or_absolute_path(
+  specifier: &str,
+) -> bool {
   if specifier.is_empty() {
     return false;
   }
@@ -1524,9 +2203,24 @@ fn to_specifier_display_string(url: &ModuleSpecifier) -> String {
   }
 }
 
-fn throw_import_not_defined(
-  specifier: &str,
-  package_json_path: Option<&Path>,
+fn package_json_load_error(err: PackageJsonLoadError) -> AnyError {
+  match err {
+    // include the file path and the parser's line and column so users can
+    // find the malformed package
+    PackageJsonLoadError::Deserialize { path, source } => {
+      errors::err_invalid_package_config(
+        &path.display().to_string(),
+        None,
+        Some(source.to_string()),
+      )
+    }
+    err => err.into(),
+  }
+}
+
+fn throw_import_not_defined(
+  specifier: &str,
+  package_json_path: Option<&Path>,
   base: &ModuleSpecifier,
 ) -> AnyError {
   errors::err_package_import_not_defined(
@@ -1633,6 +2327,16 @@ pub fn parse_npm_pkg_name(
     ".".to_string()
   };
 
+  // an empty segment (ex. "pkg//foo") would never match a key in "exports"
+  // and only resolve on disk by accident
+  if package_subpath.contains("//") {
+    return Err(errors::err_invalid_module_specifier(
+      specifier,
+      "must not contain empty path segments",
+      Some(to_specifier_display_string(referrer)),
+    ));
+  }
+
   Ok((package_name, package_subpath, is_scoped))
 }
 
@@ -1691,11 +2395,1094 @@ mod tests {
   use deno_core::serde_json::json;
 
   use super::*;
+  use crate::test_util::build_resolver;
+  use crate::test_util::build_resolver_with_fs;
+  use crate::test_util::test_path;
 
   fn build_package_json(json: Value) -> PackageJson {
     PackageJson::load_from_value(PathBuf::from("/package.json"), json)
   }
 
+  /// Resolves `subpath` against the "exports" of `/pkg/package.json` for
+  /// an ES module importer.
+  fn resolve_exports(
+    resolver: &NodeResolver,
+    exports: &Value,
+    subpath: &str,
+  ) -> Result<ModuleSpecifier, AnyError> {
+    resolver.package_exports_resolve(
+      &test_path("/pkg/package.json"),
+      subpath,
+      exports.as_object().unwrap(),
+      &to_file_specifier(&test_path("/main.js")),
+      NodeModuleKind::Esm,
+      DEFAULT_CONDITIONS,
+      NodeResolutionMode::Execution,
+    )
+  }
+
+  #[test]
+  fn test_load_package_json_malformed() {
+    let resolver =
+      build_resolver(&[("/pkg/package.json", "{\n  \"name\": \"pkg\",\n}")]);
+    let path = test_path("/pkg/package.json");
+    let err = resolver.load_package_json(&path).err().unwrap().to_string();
+    assert!(err.starts_with(&format!(
+      "[ERR_INVALID_PACKAGE_CONFIG] Invalid package config {}.",
+      path.display()
+    )));
+    assert!(err.contains("at line 3 column 1"), "{}", err);
+  }
+
+  #[test]
+  fn test_custom_conditions_reject_reserved() {
+    let err = build_resolver(&[])
+      .with_custom_conditions(vec!["custom".to_string(), "default".to_string()])
+      .err()
+      .unwrap();
+    assert_eq!(
+      err.to_string(),
+      "Invalid custom condition \"default\". The \"default\" condition is reserved and can't be overridden."
+    );
+  }
+
+  #[test]
+  fn test_custom_conditions_exports() {
+    let resolver = build_resolver(&[])
+      .with_custom_conditions(vec!["custom".to_string()])
+      .unwrap();
+    let exports = json!({
+      ".": {
+        "other": "./other.js",
+        "custom": "./custom.js",
+        "default": "./default.js",
+      },
+      "./sub": {
+        "default": "./sub-default.js",
+        "custom": "./sub-custom.js",
+      },
+    });
+    let resolve =
+      |subpath: &str| resolve_exports(&resolver, &exports, subpath).unwrap();
+    assert_eq!(
+      resolve("."),
+      to_file_specifier(&test_path("/pkg/custom.js"))
+    );
+    // "default" stays terminal
+    assert_eq!(
+      resolve("./sub"),
+      to_file_specifier(&test_path("/pkg/sub-default.js"))
+    );
+  }
+
+  #[test]
+  fn test_conditions_override() {
+    let exports = json!({
+      ".": {
+        "custom": "./custom.js",
+        "worker": "./worker.js",
+        "import": "./main.mjs",
+        "require": "./main.cjs",
+      },
+    });
+    let resolve = |resolver: &NodeResolver, conditions: &[&str]| {
+      resolver
+        .package_exports_resolve(
+          &test_path("/pkg/package.json"),
+          ".",
+          exports.as_object().unwrap(),
+          &to_file_specifier(&test_path("/main.js")),
+          NodeModuleKind::Esm,
+          conditions,
+          NodeResolutionMode::Execution,
+        )
+        .unwrap()
+    };
+
+    let resolver = build_resolver(&[])
+      .with_custom_conditions(vec!["custom".to_string()])
+      .unwrap();
+    assert_eq!(
+      resolve(&resolver, DEFAULT_CONDITIONS),
+      to_file_specifier(&test_path("/pkg/custom.js"))
+    );
+    // an override replaces the custom conditions too
+    let resolve_exact = |conditions: &[&str]| {
+      resolver
+        .package_exports_resolve_exact(
+          &test_path("/pkg/package.json"),
+          ".",
+          exports.as_object().unwrap(),
+          &to_file_specifier(&test_path("/main.js")),
+          NodeModuleKind::Esm,
+          conditions,
+          NodeResolutionMode::Execution,
+        )
+        .unwrap()
+    };
+    assert_eq!(
+      resolve_exact(&["worker"]),
+      to_file_specifier(&test_path("/pkg/worker.js"))
+    );
+    assert_eq!(
+      resolve_exact(&["require"]),
+      to_file_specifier(&test_path("/pkg/main.cjs"))
+    );
+  }
+
+  #[test]
+  fn test_module_field() {
+    let files = [
+      (
+        "/node_modules/dual/package.json",
+        r#"{ "main": "./index.cjs", "module": "./index.mjs" }"#,
+      ),
+      ("/node_modules/dual/index.cjs", ""),
+      ("/node_modules/dual/index.mjs", ""),
+      (
+        "/node_modules/missing-module/package.json",
+        r#"{ "main": "./index.cjs", "module": "./gone.mjs" }"#,
+      ),
+      ("/node_modules/missing-module/index.cjs", ""),
+    ];
+    let resolve =
+      |resolver: &NodeResolver, pkg: &str, referrer_kind: NodeModuleKind| {
+        let pkg_json = resolver
+          .load_package_json(&test_path(&format!(
+            "/node_modules/{pkg}/package.json"
+          )))
+          .unwrap()
+          .unwrap();
+        resolver
+          .resolve_package_subpath(
+            &pkg_json,
+            ".",
+            &to_file_specifier(&test_path("/main.js")),
+            referrer_kind,
+            DEFAULT_CONDITIONS,
+            NodeResolutionMode::Execution,
+          )
+          .unwrap()
+          .unwrap()
+      };
+    let expected = |path: &str| {
+      to_file_specifier(&test_path(&format!("/node_modules/{path}")))
+    };
+
+    let resolver = build_resolver(&files).with_module_field(true);
+    assert_eq!(
+      resolve(&resolver, "dual", NodeModuleKind::Esm),
+      expected("dual/index.mjs")
+    );
+    // require() keeps using "main"
+    assert_eq!(
+      resolve(&resolver, "dual", NodeModuleKind::Cjs),
+      expected("dual/index.cjs")
+    );
+    assert_eq!(
+      resolve(&resolver, "missing-module", NodeModuleKind::Esm),
+      expected("missing-module/index.cjs")
+    );
+
+    let resolver = build_resolver(&files);
+    assert_eq!(
+      resolve(&resolver, "dual", NodeModuleKind::Esm),
+      expected("dual/index.cjs")
+    );
+  }
+
+  #[test]
+  fn test_browser_field() {
+    let files = [
+      (
+        "/node_modules/with-exports/package.json",
+        r#"{
+          "exports": { ".": { "browser": "./exports-browser.js", "default": "./main.js" } },
+          "browser": "./legacy-browser.js"
+        }"#,
+      ),
+      (
+        "/node_modules/legacy/package.json",
+        r#"{
+          "main": "./main.js",
+          "browser": { "./main.js": "./browser.js", "./sub.js": "./sub-browser.js" }
+        }"#,
+      ),
+    ];
+    let conditions = &["browser", "import"];
+    let resolve = |resolver: &NodeResolver, pkg: &str, subpath: &str| {
+      let pkg_json = resolver
+        .load_package_json(&test_path(&format!(
+          "/node_modules/{pkg}/package.json"
+        )))
+        .unwrap()
+        .unwrap();
+      resolver
+        .resolve_package_subpath(
+          &pkg_json,
+          subpath,
+          &to_file_specifier(&test_path("/main.js")),
+          NodeModuleKind::Esm,
+          conditions,
+          NodeResolutionMode::Execution,
+        )
+        .unwrap()
+        .unwrap()
+    };
+    let expected = |path: &str| {
+      to_file_specifier(&test_path(&format!("/node_modules/{path}")))
+    };
+
+    let resolver = build_resolver(&files).with_browser_field(true);
+    // "exports" takes precedence over the legacy field
+    assert_eq!(
+      resolve(&resolver, "with-exports", "."),
+      expected("with-exports/exports-browser.js")
+    );
+    assert_eq!(
+      resolve(&resolver, "legacy", "."),
+      expected("legacy/browser.js")
+    );
+    assert_eq!(
+      resolve(&resolver, "legacy", "./sub"),
+      expected("legacy/sub-browser.js")
+    );
+
+    let resolver = build_resolver(&files);
+    assert_eq!(
+      resolve(&resolver, "legacy", "./sub"),
+      expected("legacy/sub")
+    );
+  }
+
+  #[test]
+  fn test_node_addons_condition() {
+    let exports = json!({
+      ".": {
+        "node-addons": "./addon.js",
+        "default": "./fallback.js",
+      },
+    });
+    let resolve = |resolver: &NodeResolver, referrer_kind| {
+      resolver
+        .package_exports_resolve(
+          &test_path("/pkg/package.json"),
+          ".",
+          exports.as_object().unwrap(),
+          &to_file_specifier(&test_path("/main.js")),
+          referrer_kind,
+          REQUIRE_CONDITIONS,
+          NodeResolutionMode::Execution,
+        )
+        .unwrap()
+    };
+    let addon = to_file_specifier(&test_path("/pkg/addon.js"));
+    let fallback = to_file_specifier(&test_path("/pkg/fallback.js"));
+
+    let resolver = build_resolver(&[]);
+    assert_eq!(resolve(&resolver, NodeModuleKind::Cjs), addon);
+    assert_eq!(resolve(&resolver, NodeModuleKind::Esm), fallback);
+    let resolver = build_resolver(&[]).with_node_addons(false);
+    assert_eq!(resolve(&resolver, NodeModuleKind::Cjs), fallback);
+  }
+
+  #[test]
+  fn test_folder_exports_compat() {
+    let exports = json!({
+      "./dir/": "./src/dir/",
+    });
+    let resolve = |resolver: &NodeResolver| {
+      resolve_exports(resolver, &exports, "./dir/x/y")
+    };
+
+    let resolver = build_resolver(&[]).with_folder_exports(true);
+    assert_eq!(
+      resolve(&resolver).unwrap(),
+      to_file_specifier(&test_path("/pkg/src/dir/x/y"))
+    );
+    let warnings = resolver.take_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].code, "DEP0148");
+    assert!(warnings[0].message.contains("\"./dir/*\""));
+    // each folder mapping only warns once
+    resolve(&resolver).unwrap();
+    assert!(resolver.take_warnings().is_empty());
+    let resolver = build_resolver(&[]);
+    let err = resolve(&resolver).unwrap_err();
+    assert!(
+      err
+        .to_string()
+        .starts_with("[ERR_PACKAGE_PATH_NOT_EXPORTED]"),
+      "{}",
+      err
+    );
+  }
+
+  #[test]
+  fn test_closest_package_json_path_without_parent() {
+    let resolver = build_resolver(&[]);
+    for path in ["", "/"] {
+      assert_eq!(
+        resolver
+          .get_closest_package_json_path(Path::new(path))
+          .unwrap(),
+        None
+      );
+      assert_eq!(
+        resolver.get_closest_tsconfig_path(Path::new(path)).unwrap(),
+        None
+      );
+    }
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_exports_target_through_symlinked_dir() {
+    use deno_fs::FileSystem;
+    use deno_fs::RealFs;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let root = RealFs.realpath_sync(temp_dir.path()).unwrap();
+    let pkg_dir = root.join("pkg");
+    let outside_dir = root.join("outside");
+    RealFs.mkdir_sync(&pkg_dir, false, 0o755).unwrap();
+    RealFs.mkdir_sync(&outside_dir, false, 0o755).unwrap();
+    RealFs
+      .write_file_sync(
+        &outside_dir.join("file.js"),
+        deno_fs::OpenOptions::write(true, false, false, None),
+        None,
+        b"",
+      )
+      .unwrap();
+    RealFs
+      .symlink_sync(&outside_dir, &pkg_dir.join("linked"), None)
+      .unwrap();
+
+    let resolver = build_resolver_with_fs(deno_fs::sync::MaybeArc::new(RealFs));
+    let exports = json!({ ".": "./linked/file.js" });
+    let resolved = resolver
+      .package_exports_resolve(
+        &pkg_dir.join("package.json"),
+        ".",
+        exports.as_object().unwrap(),
+        &to_file_specifier(&root.join("main.js")),
+        NodeModuleKind::Esm,
+        DEFAULT_CONDITIONS,
+        NodeResolutionMode::Execution,
+      )
+      .unwrap();
+    assert_eq!(
+      resolved,
+      to_file_specifier(&pkg_dir.join("linked").join("file.js"))
+    );
+  }
+
+  #[test]
+  fn test_ensure_requireable() {
+    let resolver = build_resolver(&[
+      ("/esm/package.json", r#"{ "type": "module" }"#),
+      ("/esm/index.js", ""),
+      ("/esm/index.cjs", ""),
+      ("/esm/index.mjs", ""),
+      ("/cjs/package.json", r#"{ "type": "commonjs" }"#),
+      ("/cjs/index.js", ""),
+      ("/cjs/index.cjs", ""),
+      ("/cjs/index.mjs", ""),
+    ]);
+    let esm_dir = test_path("/esm");
+    let cjs_dir = test_path("/cjs");
+    let err = resolver
+      .ensure_requireable(&esm_dir.join("index.js"), Some("/main.cjs"))
+      .unwrap_err();
+    assert_eq!(
+      deno_core::error::get_custom_error_class(&err),
+      Some("ERR_REQUIRE_ESM")
+    );
+    let err = err.to_string();
+    assert!(
+      err.starts_with(&format!(
+        "require() of ES Module {} from /main.cjs",
+        esm_dir.join("index.js").display()
+      )),
+      "{}",
+      err
+    );
+    assert!(resolver
+      .ensure_requireable(&esm_dir.join("index.cjs"), None)
+      .is_ok());
+    assert!(resolver
+      .ensure_requireable(&cjs_dir.join("index.js"), None)
+      .is_ok());
+    assert!(resolver
+      .ensure_requireable(&cjs_dir.join("index.mjs"), None)
+      .is_err());
+  }
+
+  #[test]
+  fn test_resolve_types() {
+    let resolver = build_resolver(&[
+      // the referrer's package
+      ("/app/package.json", "{}"),
+      (
+        "/app/node_modules/typed/package.json",
+        r#"{ "exports": { ".": { "types": "./types/index.d.ts", "default": "./index.js" } } }"#,
+      ),
+      ("/app/node_modules/typed/index.js", ""),
+      ("/app/node_modules/typed/types/index.d.ts", ""),
+      (
+        "/app/node_modules/untyped/package.json",
+        r#"{ "exports": { ".": "./lib/main.js" } }"#,
+      ),
+      ("/app/node_modules/untyped/lib/main.js", ""),
+      ("/app/node_modules/untyped/lib/main.d.ts", ""),
+    ]);
+    let root = test_path("/app");
+    let node_modules = root.join("node_modules");
+    let referrer = to_file_specifier(&root.join("main.ts"));
+    let resolve_types = |specifier: &str| {
+      resolver
+        .resolve(specifier, &referrer, NodeResolutionMode::Types)
+        .unwrap()
+        .unwrap()
+        .into_url()
+    };
+    // the "types" condition is matched
+    assert_eq!(
+      resolve_types("typed"),
+      to_file_specifier(&node_modules.join("typed/types/index.d.ts"))
+    );
+    // falls back to the declaration file next to the resolved module
+    assert_eq!(
+      resolve_types("untyped"),
+      to_file_specifier(&node_modules.join("untyped/lib/main.d.ts"))
+    );
+  }
+
+  #[test]
+  fn test_imports_bare_target_uses_exports() {
+    let resolver = build_resolver(&[
+      (
+        "/app/package.json",
+        r##"{ "name": "app", "imports": { "#dep": "real-dep" } }"##,
+      ),
+      (
+        "/app/node_modules/real-dep/package.json",
+        r#"{ "main": "./main.js", "exports": { ".": { "import": "./esm.mjs", "default": "./main.js" } } }"#,
+      ),
+      ("/app/node_modules/real-dep/esm.mjs", ""),
+      ("/app/node_modules/real-dep/main.js", ""),
+    ]);
+    let root = test_path("/app");
+    let real_dep_dir = root.join("node_modules/real-dep");
+    let pkg_json = resolver
+      .load_package_json(&root.join("package.json"))
+      .unwrap()
+      .unwrap();
+    let resolve = |referrer_kind, conditions| {
+      resolver
+        .package_imports_resolve(
+          "#dep",
+          &to_file_specifier(&root.join("main.js")),
+          referrer_kind,
+          Some(&*pkg_json),
+          conditions,
+          NodeResolutionMode::Execution,
+        )
+        .unwrap()
+    };
+    // the bare target goes through the exports of the package rather than
+    // being treated as a file
+    assert_eq!(
+      resolve(NodeModuleKind::Esm, DEFAULT_CONDITIONS),
+      to_file_specifier(&real_dep_dir.join("esm.mjs"))
+    );
+    assert_eq!(
+      resolve(NodeModuleKind::Cjs, REQUIRE_CONDITIONS),
+      to_file_specifier(&real_dep_dir.join("main.js"))
+    );
+  }
+
+  #[test]
+  fn test_nested_duplicate_package_prefers_closest() {
+    // non-hoisted: "a" depends on dep@1 while the root depends on dep@2
+    let resolver = build_resolver(&[
+      ("/app/package.json", r#"{ "name": "app" }"#),
+      ("/app/node_modules/a/package.json", r#"{ "name": "a" }"#),
+      ("/app/node_modules/a/index.js", ""),
+      (
+        "/app/node_modules/a/node_modules/dep/package.json",
+        r#"{ "name": "dep", "version": "1.0.0", "exports": { ".": "./v1.0.0.js" } }"#,
+      ),
+      ("/app/node_modules/a/node_modules/dep/v1.0.0.js", ""),
+      (
+        "/app/node_modules/dep/package.json",
+        r#"{ "name": "dep", "version": "2.0.0", "exports": { ".": "./v2.0.0.js" } }"#,
+      ),
+      ("/app/node_modules/dep/v2.0.0.js", ""),
+    ]);
+    let root = test_path("/app");
+    let a_dir = root.join("node_modules/a");
+    let resolve = |referrer: &Path| {
+      resolver
+        .resolve(
+          "dep",
+          &to_file_specifier(referrer),
+          NodeResolutionMode::Execution,
+        )
+        .unwrap()
+        .unwrap()
+        .into_url()
+    };
+    assert_eq!(
+      resolve(&a_dir.join("index.js")),
+      to_file_specifier(&a_dir.join("node_modules/dep/v1.0.0.js"))
+    );
+    assert_eq!(
+      resolve(&root.join("main.js")),
+      to_file_specifier(&root.join("node_modules/dep/v2.0.0.js"))
+    );
+  }
+
+  #[test]
+  fn test_conditions_follow_referrer_kind() {
+    let resolver = build_resolver(&[
+      ("/app/package.json", r#"{ "name": "app" }"#),
+      (
+        "/app/node_modules/dual/package.json",
+        r#"{ "exports": { ".": { "import": "./esm.mjs", "require": "./cjs.cjs" } } }"#,
+      ),
+      ("/app/node_modules/dual/esm.mjs", ""),
+      ("/app/node_modules/dual/cjs.cjs", ""),
+    ]);
+    let root = test_path("/app");
+    let dual_dir = root.join("node_modules/dual");
+    let resolve = |referrer: &str, referrer_kind| {
+      resolver
+        .package_resolve(
+          "dual",
+          &to_file_specifier(&root.join(referrer)),
+          referrer_kind,
+          conditions_for_kind(referrer_kind),
+          NodeResolutionMode::Execution,
+        )
+        .unwrap()
+        .unwrap()
+    };
+    assert_eq!(
+      resolve("main.cjs", NodeModuleKind::Cjs),
+      to_file_specifier(&dual_dir.join("cjs.cjs"))
+    );
+    assert_eq!(
+      resolve("main.mjs", NodeModuleKind::Esm),
+      to_file_specifier(&dual_dir.join("esm.mjs"))
+    );
+  }
+
+  #[test]
+  fn test_exports_directory_target() {
+    let resolver = build_resolver(&[
+      (
+        "/pkg/package.json",
+        r#"{ "exports": { ".": "./index.js", "./lib": "./lib", "./lib/*": "./lib/*.js", "./conf": "./conf.d" } }"#,
+      ),
+      ("/pkg/index.js", ""),
+      ("/pkg/lib/index.js", ""),
+      ("/pkg/lib/a.js", ""),
+      ("/pkg/conf.d/index.js", ""),
+    ]);
+    let pkg_json = resolver
+      .load_package_json(&test_path("/pkg/package.json"))
+      .unwrap()
+      .unwrap();
+    let resolve = |subpath: &str| {
+      resolver.package_exports_resolve(
+        &pkg_json.path,
+        subpath,
+        pkg_json.exports.as_ref().unwrap(),
+        &to_file_specifier(&test_path("/main.js")),
+        NodeModuleKind::Cjs,
+        REQUIRE_CONDITIONS,
+        NodeResolutionMode::Execution,
+      )
+    };
+
+    assert_eq!(
+      resolve("./lib/a").unwrap(),
+      to_file_specifier(&test_path("/pkg/lib/a.js"))
+    );
+    // lib/index.js isn't picked up for the directory, which require
+    // reports as a missing module
+    let err = resolve("./lib").unwrap_err();
+    assert!(err.to_string().starts_with("[MODULE_NOT_FOUND]"), "{err}");
+    // a directory with a dot in its name is still a directory
+    let err = resolve("./conf").unwrap_err();
+    assert!(err.to_string().starts_with("[MODULE_NOT_FOUND]"), "{err}");
+  }
+
+  #[test]
+  fn test_lenient_exports_keys() {
+    let resolver = build_resolver(&[]);
+    let exports = json!({
+      ".": "./index.js",
+      "sub": "./sub.js",
+    });
+    let resolve = |resolver: &NodeResolver, subpath: &str| {
+      resolve_exports(resolver, &exports, subpath)
+    };
+
+    // strict by default like Node
+    let err = resolve(&resolver, "./sub").unwrap_err().to_string();
+    assert!(
+      err.starts_with("[ERR_PACKAGE_PATH_NOT_EXPORTED]"),
+      "{}",
+      err
+    );
+
+    let resolver = resolver.with_lenient_exports_keys(true);
+    assert_eq!(
+      resolve(&resolver, "./sub").unwrap(),
+      to_file_specifier(&test_path("/pkg/sub.js"))
+    );
+    assert_eq!(
+      resolve(&resolver, ".").unwrap(),
+      to_file_specifier(&test_path("/pkg/index.js"))
+    );
+  }
+
+  #[test]
+  fn test_exports_pattern_no_index_inference() {
+    let resolver = build_resolver(&[
+      (
+        "/pkg/package.json",
+        r#"{ "exports": { "./dir/*": "./src/dir/*" } }"#,
+      ),
+      ("/pkg/src/dir/sub/index.js", ""),
+      ("/pkg/src/dir/file.js", ""),
+    ]);
+    let pkg_json = resolver
+      .load_package_json(&test_path("/pkg/package.json"))
+      .unwrap()
+      .unwrap();
+    let resolve = |subpath: &str| {
+      resolver.package_exports_resolve(
+        &pkg_json.path,
+        subpath,
+        pkg_json.exports.as_ref().unwrap(),
+        &to_file_specifier(&test_path("/main.mjs")),
+        NodeModuleKind::Esm,
+        DEFAULT_CONDITIONS,
+        NodeResolutionMode::Execution,
+      )
+    };
+
+    // the substitution is used as is without adding an extension...
+    assert_eq!(
+      resolve("./dir/file.js").unwrap(),
+      to_file_specifier(&test_path("/pkg/src/dir/file.js"))
+    );
+    // ...or probing for an index file
+    let err = resolve("./dir/sub").unwrap_err().to_string();
+    assert!(err.starts_with("[ERR_UNSUPPORTED_DIR_IMPORT]"), "{}", err);
+  }
+
+  #[test]
+  fn test_exports_backslash_target() {
+    let resolver = build_resolver(&[("/pkg/src/index.js", "")]);
+    let exports = json!({
+      ".": ".\\src\\index.js",
+      "./mixed": "./src\\index.js",
+    });
+    for subpath in [".", "./mixed"] {
+      let err = resolve_exports(&resolver, &exports, subpath)
+        .unwrap_err()
+        .to_string();
+      assert!(err.starts_with("[ERR_INVALID_PACKAGE_TARGET]"), "{}", err);
+    }
+  }
+
+  #[test]
+  fn test_exports_invalid_target_error() {
+    let resolver = build_resolver(&[("/pkg/node_modules/dep/index.js", "")]);
+    let exports = json!({
+      "./absolute": "/pkg/node_modules/dep/index.js",
+      "./nested": "./node_modules/dep/index.js",
+    });
+    for (subpath, target) in [
+      ("./absolute", "/pkg/node_modules/dep/index.js"),
+      ("./nested", "./node_modules/dep/index.js"),
+    ] {
+      let err = resolve_exports(&resolver, &exports, subpath).unwrap_err();
+      let err = err
+        .downcast_ref::<errors::InvalidPackageTargetError>()
+        .unwrap();
+      assert_eq!(err.key, subpath);
+      assert_eq!(err.target, target);
+      assert!(!err.is_import);
+    }
+  }
+
+  #[test]
+  fn test_exports_empty_string_target() {
+    let resolver = build_resolver(&[("/pkg/index.js", "")]);
+    let exports = json!({
+      ".": "",
+      "./conditional": { "import": "", "default": "./index.js" },
+    });
+    for subpath in [".", "./conditional"] {
+      let err = resolve_exports(&resolver, &exports, subpath).unwrap_err();
+      assert!(err.to_string().starts_with("[ERR_INVALID_PACKAGE_TARGET]"));
+      let err = err
+        .downcast_ref::<errors::InvalidPackageTargetError>()
+        .unwrap();
+      assert_eq!(err.key, subpath);
+      assert_eq!(err.target, "");
+    }
+  }
+
+  #[test]
+  fn test_exports_dual_package_by_kind() {
+    let resolver =
+      build_resolver(&[("/pkg/index.cjs", ""), ("/pkg/index.mjs", "")]);
+    // "require" is listed first so a shared condition list would pick it
+    let exports = json!({
+      ".": { "require": "./index.cjs", "import": "./index.mjs" },
+    });
+    let resolve = |kind| {
+      resolver
+        .package_exports_resolve(
+          &test_path("/pkg/package.json"),
+          ".",
+          exports.as_object().unwrap(),
+          &to_file_specifier(&test_path("/main.js")),
+          kind,
+          conditions_for_kind(kind),
+          NodeResolutionMode::Execution,
+        )
+        .unwrap()
+    };
+    assert!(!DEFAULT_CONDITIONS.contains(&"require"));
+    assert!(!REQUIRE_CONDITIONS.contains(&"import"));
+    assert_eq!(
+      resolve(NodeModuleKind::Esm),
+      to_file_specifier(&test_path("/pkg/index.mjs"))
+    );
+    assert_eq!(
+      resolve(NodeModuleKind::Cjs),
+      to_file_specifier(&test_path("/pkg/index.cjs"))
+    );
+    // neither can be added back through custom conditions
+    for condition in ["import", "require"] {
+      assert!(build_resolver(&[])
+        .with_custom_conditions(vec![condition.to_string()])
+        .is_err());
+    }
+  }
+
+  #[test]
+  fn test_exports_subpath_below_condition() {
+    let resolver = build_resolver(&[("/pkg/x.js", "")]);
+    for exports in [
+      // the normalized form of `{ "node": { "./sub": "./x.js" } }`
+      json!({ ".": { "node": { "./sub": "./x.js" } } }),
+      json!({ ".": { "import": { ".": "./x.js" } } }),
+    ] {
+      let err = resolve_exports(&resolver, &exports, ".").unwrap_err();
+      let message = err.to_string();
+      assert!(
+        message.starts_with("[ERR_INVALID_PACKAGE_CONFIG]"),
+        "{message}"
+      );
+      assert!(
+        message.contains("cannot contain the subpath key"),
+        "{message}"
+      );
+    }
+  }
+
+  #[test]
+  fn test_exports_mixed_array_by_mode() {
+    let resolver = build_resolver(&[("/pkg/a.d.ts", ""), ("/pkg/b.js", "")]);
+    let exports = json!({
+      ".": [{ "types": "./a.d.ts" }, "./b.js"],
+    });
+    let resolve = |mode| {
+      resolver
+        .package_exports_resolve(
+          &test_path("/pkg/package.json"),
+          ".",
+          exports.as_object().unwrap(),
+          &to_file_specifier(&test_path("/main.ts")),
+          NodeModuleKind::Esm,
+          DEFAULT_CONDITIONS,
+          mode,
+        )
+        .unwrap()
+    };
+    assert_eq!(
+      resolve(NodeResolutionMode::Types),
+      to_file_specifier(&test_path("/pkg/a.d.ts"))
+    );
+    // the types only object matches no condition, so it's skipped
+    assert_eq!(
+      resolve(NodeResolutionMode::Execution),
+      to_file_specifier(&test_path("/pkg/b.js"))
+    );
+  }
+
+  #[test]
+  fn test_exports_nesting_too_deep() {
+    let resolver = build_resolver(&[("/pkg/index.js", "")]);
+    let mut target = json!("./index.js");
+    for _ in 0..=MAX_PACKAGE_TARGET_DEPTH {
+      target = json!({ "import": target });
+    }
+    let exports = json!({ ".": target });
+    let err = resolve_exports(&resolver, &exports, ".").unwrap_err();
+    let message = err.to_string();
+    assert!(
+      message.starts_with("[ERR_INVALID_PACKAGE_CONFIG]"),
+      "{message}"
+    );
+    assert!(
+      message.contains("nested more than 32 levels deep"),
+      "{message}"
+    );
+
+    // arrays count towards the depth as well
+    let mut target = json!("./index.js");
+    for _ in 0..=MAX_PACKAGE_TARGET_DEPTH {
+      target = json!([target]);
+    }
+    let exports = json!({ ".": target });
+    assert!(resolve_exports(&resolver, &exports, ".").is_err());
+
+    // just within the limit still resolves
+    let mut target = json!("./index.js");
+    for _ in 0..MAX_PACKAGE_TARGET_DEPTH {
+      target = json!({ "import": target });
+    }
+    let exports = json!({ ".": target });
+    assert_eq!(
+      resolve_exports(&resolver, &exports, ".").unwrap(),
+      to_file_specifier(&test_path("/pkg/index.js"))
+    );
+  }
+
+  #[test]
+  fn test_node_env_conditions() {
+    let exports = json!({
+      ".": {
+        "development": "./dev.js",
+        "production": "./prod.js",
+        "default": "./index.js",
+      },
+    });
+    let resolve = |node_env: Option<&str>| {
+      let resolver = build_resolver(&[]).with_node_env(node_env);
+      resolve_exports(&resolver, &exports, ".").unwrap()
+    };
+    assert_eq!(
+      resolve(Some("production")),
+      to_file_specifier(&test_path("/pkg/prod.js"))
+    );
+    assert_eq!(
+      resolve(Some("development")),
+      to_file_specifier(&test_path("/pkg/dev.js"))
+    );
+    assert_eq!(
+      resolve(Some("test")),
+      to_file_specifier(&test_path("/pkg/index.js"))
+    );
+    assert_eq!(
+      resolve(None),
+      to_file_specifier(&test_path("/pkg/index.js"))
+    );
+  }
+
+  #[test]
+  fn test_allowed_conditions() {
+    let resolver = build_resolver(&[]).with_allowed_conditions(Some(
+      ["import".to_string()].into_iter().collect(),
+    ));
+    let exports = json!({
+      ".": {
+        "node": "./evil.js",
+        "import": "./index.mjs",
+        "default": "./index.js",
+      },
+      "./sub": {
+        "node": "./evil.js",
+        "default": "./sub.js",
+      },
+    });
+    let resolve =
+      |subpath: &str| resolve_exports(&resolver, &exports, subpath).unwrap();
+    // "node" is active but not allowed, so its branch is skipped
+    assert_eq!(
+      resolve("."),
+      to_file_specifier(&test_path("/pkg/index.mjs"))
+    );
+    assert_eq!(
+      resolve("./sub"),
+      to_file_specifier(&test_path("/pkg/sub.js"))
+    );
+  }
+
+  #[test]
+  fn test_exports_root_not_matched_by_pattern() {
+    let resolver = build_resolver(&[]);
+    let exports = json!({
+      ".": "./main.js",
+      "./*": "./src/*",
+    });
+    let resolve =
+      |subpath: &str| resolve_exports(&resolver, &exports, subpath).unwrap();
+    // the bare name only ever matches "."
+    assert_eq!(resolve("."), to_file_specifier(&test_path("/pkg/main.js")));
+    // while an explicit subpath goes through the pattern, even when it
+    // names the same file as "."
+    assert_eq!(
+      resolve("./main.js"),
+      to_file_specifier(&test_path("/pkg/src/main.js"))
+    );
+    assert_eq!(
+      resolve("./util.js"),
+      to_file_specifier(&test_path("/pkg/src/util.js"))
+    );
+  }
+
+  #[test]
+  fn test_exports_array_fallback() {
+    let resolver = build_resolver(&[("/pkg/b.d.ts", "")]);
+    let exports = json!({
+      ".": { "import": ["./a.mjs", "./b.js"] },
+      "./invalid": ["../outside.js", "./valid.js"],
+      "./all-invalid": ["../a.js", "../b.js"],
+    });
+    let resolve = |subpath: &str, mode| {
+      resolver.package_exports_resolve(
+        &test_path("/pkg/package.json"),
+        subpath,
+        exports.as_object().unwrap(),
+        &to_file_specifier(&test_path("/main.js")),
+        NodeModuleKind::Esm,
+        DEFAULT_CONDITIONS,
+        mode,
+      )
+    };
+
+    // the first entry has no declaration file, so the second one is used
+    assert_eq!(
+      resolve(".", NodeResolutionMode::Types).unwrap(),
+      to_file_specifier(&test_path("/pkg/b.d.ts"))
+    );
+    // like Node, the existence of a file isn't checked during execution so
+    // the first valid entry wins
+    assert_eq!(
+      resolve(".", NodeResolutionMode::Execution).unwrap(),
+      to_file_specifier(&test_path("/pkg/a.mjs"))
+    );
+    // invalid targets are skipped
+    assert_eq!(
+      resolve("./invalid", NodeResolutionMode::Execution).unwrap(),
+      to_file_specifier(&test_path("/pkg/valid.js"))
+    );
+    // when no entry is valid, the last error is surfaced
+    let err = resolve("./all-invalid", NodeResolutionMode::Execution)
+      .unwrap_err()
+      .to_string();
+    assert!(err.starts_with("[ERR_INVALID_PACKAGE_TARGET]"), "{}", err);
+  }
+
+  #[test]
+  fn test_cjs_exports_entry_in_module_package() {
+    let resolver = build_resolver(&[(
+      "/pkg/package.json",
+      r#"{ "name": "pkg", "type": "module", "exports": { ".": "./main.cjs" } }"#,
+    )]);
+    let pkg_json = resolver
+      .load_package_json(&test_path("/pkg/package.json"))
+      .unwrap()
+      .unwrap();
+    let url = resolver
+      .package_exports_resolve(
+        &pkg_json.path,
+        ".",
+        pkg_json.exports.as_ref().unwrap(),
+        &to_file_specifier(&test_path("/main.js")),
+        NodeModuleKind::Esm,
+        DEFAULT_CONDITIONS,
+        NodeResolutionMode::Execution,
+      )
+      .unwrap();
+    assert_eq!(url, to_file_specifier(&test_path("/pkg/main.cjs")));
+    // the package's "type" doesn't force the .cjs file to be ESM
+    assert!(matches!(
+      resolver.url_to_node_resolution(url).unwrap(),
+      NodeResolution::CommonJs(_)
+    ));
+  }
+
+  #[test]
+  fn test_exports_root_and_subpaths() {
+    let resolver = build_resolver(&[
+      (
+        "/mixed/package.json",
+        r#"{ "exports": { ".": "./main.js", "./sub": "./sub.js" } }"#,
+      ),
+      ("/sugar/package.json", r#"{ "exports": "./main.js" }"#),
+      (
+        "/conditional-sugar/package.json",
+        r#"{ "exports": { "import": "./main.mjs", "require": "./main.cjs" } }"#,
+      ),
+    ]);
+    let resolve = |pkg: &str, subpath: &str| {
+      let pkg_json = resolver
+        .load_package_json(&test_path(&format!("/{pkg}/package.json")))
+        .unwrap()
+        .unwrap();
+      resolver.package_exports_resolve(
+        &pkg_json.path,
+        subpath,
+        pkg_json.exports.as_ref().unwrap(),
+        &to_file_specifier(&test_path("/main.js")),
+        NodeModuleKind::Esm,
+        DEFAULT_CONDITIONS,
+        NodeResolutionMode::Execution,
+      )
+    };
+
+    assert_eq!(
+      resolve("mixed", ".").unwrap(),
+      to_file_specifier(&test_path("/mixed/main.js"))
+    );
+    assert_eq!(
+      resolve("mixed", "./sub").unwrap(),
+      to_file_specifier(&test_path("/mixed/sub.js"))
+    );
+    assert_eq!(
+      resolve("sugar", ".").unwrap(),
+      to_file_specifier(&test_path("/sugar/main.js"))
+    );
+    assert_eq!(
+      resolve("conditional-sugar", ".").unwrap(),
+      to_file_specifier(&test_path("/conditional-sugar/main.mjs"))
+    );
+    for (pkg, subpath) in [
+      ("mixed", "./other"),
+      ("mixed", "./sub/deep"),
+      ("sugar", "./main.js"),
+      ("conditional-sugar", "./import"),
+    ] {
+      let err = resolve(pkg, subpath).unwrap_err().to_string();
+      assert!(
+        err.starts_with("[ERR_PACKAGE_PATH_NOT_EXPORTED]"),
+        "{pkg} {subpath}: {err}"
+      );
+    }
+  }
+
   #[test]
   fn test_resolve_bin_entry_value() {
     // should resolve the specified value
@@ -1853,6 +3640,208 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_parse_package_name_empty_segments() {
+    let referrer = to_file_specifier(&test_path("/main.js"));
+
+    for specifier in ["pkg//foo", "pkg/foo//bar", "@scope/pkg//foo"] {
+      let err = parse_npm_pkg_name(specifier, &referrer).unwrap_err();
+      assert_eq!(
+        err.to_string(),
+        format!(
+          "[ERR_INVALID_MODULE_SPECIFIER] Invalid module \"{specifier}\" must not contain empty path segments imported from {}",
+          to_specifier_display_string(&referrer)
+        )
+      );
+    }
+    // a trailing slash is not an empty segment
+    assert_eq!(
+      parse_npm_pkg_name("pkg/foo/", &referrer).unwrap(),
+      ("pkg".to_string(), "./foo/".to_string(), false)
+    );
+  }
+
+  #[test]
+  fn test_parse_package_name_with_dots() {
+    let dummy_referrer = Url::parse("http://example.com").unwrap();
+
+    assert_eq!(
+      parse_npm_pkg_name("lodash.merge", &dummy_referrer).unwrap(),
+      ("lodash.merge".to_string(), ".".to_string(), false)
+    );
+    assert_eq!(
+      parse_npm_pkg_name("lodash.merge/fp", &dummy_referrer).unwrap(),
+      ("lodash.merge".to_string(), "./fp".to_string(), false)
+    );
+    assert_eq!(
+      parse_npm_pkg_name("@org/my.pkg/sub", &dummy_referrer).unwrap(),
+      ("@org/my.pkg".to_string(), "./sub".to_string(), true)
+    );
+    assert_eq!(
+      parse_npm_pkg_name("my_pkg-name.js", &dummy_referrer).unwrap(),
+      ("my_pkg-name.js".to_string(), ".".to_string(), false)
+    );
+  }
+
+  #[test]
+  fn test_empty_exports_object() {
+    let resolver = build_resolver(&[
+      ("/app/package.json", r#"{ "name": "app" }"#),
+      (
+        "/app/node_modules/empty/package.json",
+        r#"{ "main": "./index.js", "exports": {} }"#,
+      ),
+      ("/app/node_modules/empty/index.js", ""),
+      ("/app/node_modules/empty/sub.js", ""),
+      (
+        "/app/node_modules/absent/package.json",
+        r#"{ "main": "./index.js" }"#,
+      ),
+      ("/app/node_modules/absent/index.js", ""),
+      ("/app/node_modules/absent/sub.js", ""),
+    ]);
+    let root = test_path("/app");
+    let resolve = |specifier: &str| {
+      resolver.package_resolve(
+        specifier,
+        &to_file_specifier(&root.join("main.js")),
+        NodeModuleKind::Cjs,
+        REQUIRE_CONDITIONS,
+        NodeResolutionMode::Execution,
+      )
+    };
+    // an empty "exports" object exports nothing
+    for specifier in ["empty", "empty/sub.js"] {
+      let err = resolve(specifier).unwrap_err().to_string();
+      assert!(
+        err.starts_with("[ERR_PACKAGE_PATH_NOT_EXPORTED]"),
+        "{specifier}: {err}"
+      );
+    }
+    // while without "exports" the legacy resolution applies
+    assert_eq!(
+      resolve("absent").unwrap().unwrap(),
+      to_file_specifier(&root.join("node_modules/absent/index.js"))
+    );
+    assert_eq!(
+      resolve("absent/sub.js").unwrap().unwrap(),
+      to_file_specifier(&root.join("node_modules/absent/sub.js"))
+    );
+  }
+
+  #[test]
+  fn test_resolve_dotted_package_names() {
+    let resolver = build_resolver(&[
+      (
+        "/app/package.json",
+        r#"{ "name": "@org/my.pkg", "exports": { "./sub": "./sub.js" } }"#,
+      ),
+      ("/app/sub.js", ""),
+      (
+        "/app/node_modules/lodash.merge/package.json",
+        r#"{ "name": "lodash.merge", "exports": { ".": "./index.js", "./fp": "./fp.js" } }"#,
+      ),
+      ("/app/node_modules/lodash.merge/index.js", ""),
+      ("/app/node_modules/lodash.merge/fp.js", ""),
+    ]);
+    let root = test_path("/app");
+    let merge_dir = root.join("node_modules/lodash.merge");
+    let resolve = |specifier: &str| {
+      resolver
+        .package_resolve(
+          specifier,
+          &to_file_specifier(&root.join("main.js")),
+          NodeModuleKind::Cjs,
+          REQUIRE_CONDITIONS,
+          NodeResolutionMode::Execution,
+        )
+        .unwrap()
+        .unwrap()
+    };
+    assert_eq!(
+      resolve("lodash.merge"),
+      to_file_specifier(&merge_dir.join("index.js"))
+    );
+    assert_eq!(
+      resolve("lodash.merge/fp"),
+      to_file_specifier(&merge_dir.join("fp.js"))
+    );
+    // self reference through the scoped, dotted name
+    assert_eq!(
+      resolve("@org/my.pkg/sub"),
+      to_file_specifier(&root.join("sub.js"))
+    );
+  }
+
+  #[test]
+  fn test_parse_package_name_never_splits_scope() {
+    let dummy_referrer = Url::parse("http://example.com").unwrap();
+
+    // a scoped package name always includes the scope and the name
+    assert_eq!(
+      parse_npm_pkg_name("@a/b", &dummy_referrer).unwrap(),
+      ("@a/b".to_string(), ".".to_string(), true)
+    );
+    assert_eq!(
+      parse_npm_pkg_name("@a/b/c", &dummy_referrer).unwrap(),
+      ("@a/b".to_string(), "./c".to_string(), true)
+    );
+    // a scope on its own is not a valid package name
+    assert!(parse_npm_pkg_name("@a", &dummy_referrer).is_err());
+  }
+
+  #[test]
+  fn test_resolve_extra_polyfill_as_builtin() {
+    let resolver = build_resolver(&[(
+      "/app/package.json",
+      r##"{ "name": "app", "imports": { "#db": "sqlite" } }"##,
+    )])
+    .with_extra_polyfills(vec![NodeModulePolyfill {
+      name: "sqlite".to_string(),
+      specifier: "ext:my_embedder/sqlite.js".to_string(),
+    }]);
+    let referrer = to_file_specifier(&test_path("/app/main.js"));
+    assert!(matches!(
+      resolver
+        .resolve("sqlite", &referrer, NodeResolutionMode::Execution)
+        .unwrap(),
+      Some(NodeResolution::BuiltIn(name)) if name == "sqlite"
+    ));
+    // stock builtins are still resolved
+    assert!(matches!(
+      resolver
+        .resolve("fs", &referrer, NodeResolutionMode::Execution)
+        .unwrap(),
+      Some(NodeResolution::BuiltIn(name)) if name == "fs"
+    ));
+
+    let pkg_json = resolver
+      .load_package_json(&test_path("/app/package.json"))
+      .unwrap()
+      .unwrap();
+    assert_eq!(
+      resolver
+        .package_imports_resolve(
+          "#db",
+          &referrer,
+          NodeModuleKind::Esm,
+          Some(&*pkg_json),
+          DEFAULT_CONDITIONS,
+          NodeResolutionMode::Execution,
+        )
+        .unwrap()
+        .as_str(),
+      "node:sqlite"
+    );
+
+    // without the polyfill the name is an ordinary bare specifier
+    let resolver = build_resolver(&[]);
+    assert!(!matches!(
+      resolver.resolve("sqlite", &referrer, NodeResolutionMode::Execution),
+      Ok(Some(NodeResolution::BuiltIn(_)))
+    ));
+  }
+
   #[test]
   fn test_with_known_extension() {
     let cases = &[
@@ -0,0 +1,104 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
+use deno_core::ModuleSpecifier;
+use deno_fs::FileSystemRc;
+use deno_fs::InMemoryFs;
+
+use crate::resolution::NodeResolver;
+use crate::NodePermissions;
+use crate::NpmResolver;
+use crate::PackageJsonThreadLocalCache;
+
+/// Creates an in memory file system with the given text files. Tests only
+/// need a real file system for symlinks and case insensitivity.
+pub fn in_memory_fs(files: &[(impl AsRef<Path>, &str)]) -> InMemoryFs {
+  let fs = InMemoryFs::default();
+  fs.setup_text_files(
+    files
+      .iter()
+      .map(|(path, text)| {
+        (
+          path.as_ref().to_string_lossy().to_string(),
+          text.to_string(),
+        )
+      })
+      .collect(),
+  );
+  fs
+}
+
+/// Gets an absolute path for the current platform from a unix style path.
+pub fn test_path(path: &str) -> PathBuf {
+  if cfg!(windows) {
+    PathBuf::from(format!("C:{}", path.replace('/', "\\")))
+  } else {
+    PathBuf::from(path)
+  }
+}
+
+/// Resolves packages by walking up the `node_modules` directories of
+/// the referrer in the provided file system.
+#[derive(Debug)]
+pub struct TestNpmResolver {
+  fs: FileSystemRc,
+}
+
+impl TestNpmResolver {
+  pub fn new(fs: FileSystemRc) -> Self {
+    Self { fs }
+  }
+}
+
+impl NpmResolver for TestNpmResolver {
+  fn resolve_package_folder_from_package(
+    &self,
+    specifier: &str,
+    referrer: &ModuleSpecifier,
+  ) -> Result<PathBuf, AnyError> {
+    let referrer_path = referrer.to_file_path().unwrap();
+    for ancestor in referrer_path.ancestors().skip(1) {
+      let package_folder = ancestor.join("node_modules").join(specifier);
+      if self.fs.is_dir_sync(&package_folder) {
+        return Ok(package_folder);
+      }
+    }
+    bail!("could not find package '{}'", specifier)
+  }
+
+  fn in_npm_package(&self, specifier: &ModuleSpecifier) -> bool {
+    specifier.path().contains("/node_modules/")
+  }
+
+  fn ensure_read_permission(
+    &self,
+    _permissions: &mut dyn NodePermissions,
+    _path: &Path,
+  ) -> Result<(), AnyError> {
+    Ok(())
+  }
+}
+
+/// Creates a `NodeResolver` over an in memory file system with the given
+/// text files, whose paths are passed through `test_path`.
+pub fn build_resolver(files: &[(&str, &str)]) -> NodeResolver {
+  let files = files
+    .iter()
+    .map(|(path, text)| (test_path(path), *text))
+    .collect::<Vec<_>>();
+  build_resolver_with_fs(deno_fs::sync::MaybeArc::new(in_memory_fs(&files)))
+}
+
+/// Creates a `NodeResolver` over the provided file system, starting from an
+/// empty package.json cache.
+pub fn build_resolver_with_fs(fs: FileSystemRc) -> NodeResolver {
+  PackageJsonThreadLocalCache::clear();
+  NodeResolver::new(
+    fs.clone(),
+    deno_fs::sync::MaybeArc::new(TestNpmResolver::new(fs)),
+  )
+}
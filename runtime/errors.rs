@@ -198,6 +198,14 @@ pub fn get_error_class_name(e: &AnyError) -> Option<&'static str> {
       e.downcast_ref::<ModuleResolutionError>()
         .map(get_module_resolution_error_class)
     })
+    .or_else(|| {
+      e.downcast_ref::<deno_node::errors::InvalidPackageTargetError>()
+        .map(|_| "Error")
+    })
+    .or_else(|| {
+      e.downcast_ref::<deno_node::errors::PackagePathNotExportedError>()
+        .map(|_| "Error")
+    })
     .or_else(|| {
       e.downcast_ref::<notify::Error>()
         .map(get_notify_error_class)
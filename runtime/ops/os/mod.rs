@@ -8,6 +8,7 @@ use deno_core::op2;
 use deno_core::url::Url;
 use deno_core::v8;
 use deno_core::OpState;
+use deno_node::EnvVarAllowlist;
 use deno_node::NODE_ENV_VAR_ALLOWLIST;
 use deno_permissions::PermissionsContainer;
 use serde::Serialize;
@@ -125,7 +126,10 @@ fn op_get_env(
   state: &mut OpState,
   #[string] key: String,
 ) -> Result<Option<String>, AnyError> {
-  let skip_permission_check = NODE_ENV_VAR_ALLOWLIST.contains(&key);
+  let skip_permission_check = match state.try_borrow::<EnvVarAllowlist>() {
+    Some(allowlist) => allowlist.contains(&key),
+    None => NODE_ENV_VAR_ALLOWLIST.contains(&key),
+  };
 
   if !skip_permission_check {
     state.borrow_mut::<PermissionsContainer>().check_env(&key)?;
@@ -2,9 +2,12 @@
 
 use std::io::Error;
 
+use deno_core::error::generic_error;
 use deno_core::error::AnyError;
 use deno_core::op2;
 use deno_core::OpState;
+use deno_core::ResourceHandleFd;
+use deno_core::ResourceId;
 use rustyline::config::Configurer;
 use rustyline::error::ReadlineError;
 use rustyline::Cmd;
@@ -20,8 +23,6 @@ use deno_io::WinTtyState;
 #[cfg(windows)]
 use std::sync::Arc;
 
-#[cfg(unix)]
-use deno_core::ResourceId;
 #[cfg(unix)]
 use nix::sys::termios;
 #[cfg(unix)]
@@ -57,7 +58,13 @@ use winapi::um::wincon;
 
 deno_core::extension!(
   deno_tty,
-  ops = [op_set_raw, op_console_size, op_read_line_prompt],
+  ops = [
+    op_set_raw,
+    op_console_size,
+    op_read_line_prompt,
+    op_node_tty_window_size,
+    op_node_tty_set_raw_mode,
+  ],
   state = |state| {
     #[cfg(unix)]
     state.put(TtyModeStore::default());
@@ -90,6 +97,15 @@ fn op_set_raw(
   rid: u32,
   is_raw: bool,
   cbreak: bool,
+) -> Result<(), AnyError> {
+  set_raw(state, rid, is_raw, cbreak)
+}
+
+fn set_raw(
+  state: &mut OpState,
+  rid: u32,
+  is_raw: bool,
+  cbreak: bool,
 ) -> Result<(), AnyError> {
   let handle_or_fd = state.resource_table.get_fd(rid)?;
 
@@ -315,6 +331,54 @@ fn op_console_size(
   last_result
 }
 
+fn err_not_a_tty() -> AnyError {
+  generic_error("[ENOTTY] inappropriate ioctl for device")
+}
+
+/// Returns `[columns, rows]` of the terminal behind `rid` for `node:tty`.
+#[op2]
+#[serde]
+fn op_node_tty_window_size(
+  state: &mut OpState,
+  #[smi] rid: ResourceId,
+) -> Result<[u32; 2], AnyError> {
+  let handle_or_fd = state.resource_table.get_fd(rid)?;
+  window_size(handle_or_fd)
+}
+
+fn window_size(handle_or_fd: ResourceHandleFd) -> Result<[u32; 2], AnyError> {
+  let size = console_size_from_fd(handle_or_fd).map_err(|_| err_not_a_tty())?;
+  Ok([size.cols, size.rows])
+}
+
+/// `setRawMode` for `node:tty`. It goes through `op_set_raw`, so the mode
+/// saved by either one is restored by the other.
+#[op2(fast)]
+fn op_node_tty_set_raw_mode(
+  state: &mut OpState,
+  #[smi] rid: ResourceId,
+  raw: bool,
+) -> Result<(), AnyError> {
+  let handle_or_fd = state.resource_table.get_fd(rid)?;
+  if !is_terminal(handle_or_fd) {
+    return Err(err_not_a_tty());
+  }
+  set_raw(state, rid, raw, false)
+}
+
+#[cfg(unix)]
+fn is_terminal(fd: ResourceHandleFd) -> bool {
+  // SAFETY: libc call
+  unsafe { libc::isatty(fd) == 1 }
+}
+
+#[cfg(windows)]
+fn is_terminal(handle: ResourceHandleFd) -> bool {
+  let mut mode = 0;
+  // SAFETY: winapi call
+  unsafe { winapi::um::consoleapi::GetConsoleMode(handle, &mut mode) != 0 }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct ConsoleSize {
   pub cols: u32,
@@ -375,6 +439,37 @@ fn console_size_from_fd(
   }
 }
 
+#[cfg(all(test, unix))]
+mod tests {
+  use std::io::IsTerminal;
+
+  use super::*;
+
+  #[test]
+  fn test_node_tty_window_size_pipe() {
+    let mut fds = [0; 2];
+    // SAFETY: libc call, `fds` has room for both ends of the pipe.
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    let err = window_size(fds[0]).unwrap_err();
+    assert_eq!(err.to_string(), "[ENOTTY] inappropriate ioctl for device");
+    assert!(!is_terminal(fds[1]));
+    for fd in fds {
+      // SAFETY: closing the fds opened above.
+      unsafe { libc::close(fd) };
+    }
+  }
+
+  #[test]
+  fn test_node_tty_window_size_tty() {
+    // only meaningful when the tests run in a terminal, which CI doesn't
+    if !std::io::stdout().is_terminal() {
+      return;
+    }
+    let [columns, rows] = window_size(libc::STDOUT_FILENO).unwrap();
+    assert!(columns > 0 && rows > 0);
+  }
+}
+
 #[cfg(all(test, windows))]
 mod tests {
   #[test]
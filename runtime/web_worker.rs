@@ -495,6 +495,7 @@ impl WebWorker {
         options.node_resolver,
         options.npm_resolver,
         options.fs,
+        Default::default(),
       ),
       // Runtime ops that are always initialized for WebWorkers
       ops::runtime::deno_runtime::init_ops_and_esm(main_module.clone()),
@@ -421,6 +421,7 @@ impl MainWorker {
         options.node_resolver,
         options.npm_resolver,
         options.fs,
+        Default::default(),
       ),
       // Ops from this crate
       ops::runtime::deno_runtime::init_ops_and_esm(main_module.clone()),
@@ -921,3 +922,89 @@ impl MainWorker {
     Ok(ret_val.is_true())
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use std::path::Path;
+  use std::path::PathBuf;
+
+  use deno_core::anyhow::bail;
+
+  use super::*;
+
+  deno_core::extension!(
+    sqlite_polyfill,
+    lazy_loaded_esm = ["ext:sqlite_polyfill/sqlite.js" =
+      { source = "export default { open: (path) => `db:${path}` };" }],
+  );
+
+  #[derive(Debug)]
+  struct NoNpmResolver;
+
+  impl deno_node::NpmResolver for NoNpmResolver {
+    fn resolve_package_folder_from_package(
+      &self,
+      specifier: &str,
+      _referrer: &ModuleSpecifier,
+    ) -> Result<PathBuf, AnyError> {
+      bail!("could not find package '{}'", specifier)
+    }
+
+    fn in_npm_package(&self, _specifier: &ModuleSpecifier) -> bool {
+      false
+    }
+
+    fn ensure_read_permission(
+      &self,
+      _permissions: &mut dyn deno_node::NodePermissions,
+      _path: &Path,
+    ) -> Result<(), AnyError> {
+      Ok(())
+    }
+  }
+
+  #[tokio::test]
+  async fn require_extra_polyfill() {
+    let fs: Arc<dyn FileSystem> = Arc::new(deno_fs::RealFs);
+    let npm_resolver: Arc<dyn deno_node::NpmResolver> = Arc::new(NoNpmResolver);
+    let node_resolver = Arc::new(
+      deno_node::NodeResolver::new(fs.clone(), npm_resolver.clone())
+        .with_extra_polyfills(vec![deno_node::NodeModulePolyfill {
+          name: "sqlite".to_string(),
+          specifier: "ext:sqlite_polyfill/sqlite.js".to_string(),
+        }]),
+    );
+    let main_module = ModuleSpecifier::parse("file:///main.js").unwrap();
+    let mut worker = MainWorker::bootstrap_from_options(
+      main_module,
+      PermissionsContainer::allow_all(),
+      WorkerOptions {
+        extensions: vec![sqlite_polyfill::init_ops_and_esm()],
+        fs,
+        node_resolver: Some(node_resolver),
+        npm_resolver: Some(npm_resolver),
+        ..Default::default()
+      },
+    );
+    worker
+      .execute_script(
+        "require_sqlite.js",
+        ModuleCodeString::from_static(
+          r#"
+          const { Module } = Deno[Deno.internal].requireImpl;
+          const sqlite = Module._load("sqlite", null, false);
+          if (sqlite.open("a") !== "db:a") {
+            throw new Error("unexpected exports");
+          }
+          if (Module._load("node:sqlite", null, false) !== sqlite) {
+            throw new Error("node: prefix loaded a different module");
+          }
+          if (!Module.isBuiltin("sqlite")) {
+            throw new Error("not reported as a builtin");
+          }
+          "#,
+        ),
+      )
+      .unwrap();
+  }
+}
@@ -1,6 +1,11 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 import * as fs from "node:fs";
-import { assertRejects, assertThrows } from "@std/assert/mod.ts";
+import * as path from "@std/path/mod.ts";
+import {
+  assertEquals,
+  assertRejects,
+  assertThrows,
+} from "@std/assert/mod.ts";
 
 Deno.test(
   "[node/fs.access] Uses the owner permission when the user is the owner",
@@ -65,3 +70,42 @@ Deno.test(
     }
   },
 );
+
+Deno.test(
+  "[node/fs.accessSync] Applies the group permission to group members",
+  { ignore: Deno.build.os === "windows" || Deno.uid() === 0 },
+  () => {
+    const file = Deno.makeTempFileSync();
+    try {
+      // the owner has no access even though the group may read
+      Deno.chmodSync(file, 0o040);
+      const err = assertThrows(() => {
+        fs.accessSync(file, fs.constants.R_OK);
+      });
+      // deno-lint-ignore no-explicit-any
+      assertEquals((err as any).code, "EACCES");
+      assertEquals(
+        (err as Error).message,
+        `EACCES: permission denied, access '${file}'`,
+      );
+    } finally {
+      Deno.removeSync(file);
+    }
+  },
+);
+
+Deno.test("[node/fs.access] Reports a missing file as ENOENT", async () => {
+  const dir = await Deno.makeTempDir();
+  const missing = path.join(dir, "missing.txt");
+  try {
+    const err = await new Promise<Error | null>((resolve) => {
+      fs.access(missing, fs.constants.F_OK, resolve);
+    });
+    // deno-lint-ignore no-explicit-any
+    assertEquals((err as any)?.code, "ENOENT");
+    // deno-lint-ignore no-explicit-any
+    assertEquals((err as any)?.syscall, "access");
+  } finally {
+    await Deno.remove(dir, { recursive: true });
+  }
+});
@@ -1,8 +1,14 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 import * as path from "@std/path/mod.ts";
-import { assert } from "@std/assert/mod.ts";
+import { assert, assertEquals, assertThrows } from "@std/assert/mod.ts";
 import { assertCallbackErrorUncaught } from "../_test_utils.ts";
-import { copyFile, copyFileSync, cpSync, existsSync } from "node:fs";
+import {
+  constants,
+  copyFile,
+  copyFileSync,
+  cpSync,
+  existsSync,
+} from "node:fs";
 
 const destFile = "./destination.txt";
 
@@ -31,6 +37,47 @@ Deno.test({
   },
 });
 
+Deno.test({
+  name: "[std/node/fs] copy file sync with COPYFILE_EXCL",
+  fn: () => {
+    const tempDir = Deno.makeTempDirSync();
+    const src = path.join(tempDir, "src.txt");
+    const dest = path.join(tempDir, "dest.txt");
+    Deno.writeTextFileSync(src, "hello");
+    try {
+      copyFileSync(src, dest, constants.COPYFILE_EXCL);
+      assertEquals(Deno.readTextFileSync(dest), "hello");
+
+      const err = assertThrows(() =>
+        copyFileSync(src, dest, constants.COPYFILE_EXCL)
+      );
+      // deno-lint-ignore no-explicit-any
+      assertEquals((err as any).code, "EEXIST");
+      assertEquals(
+        (err as Error).message,
+        `EEXIST: file already exists, copyfile '${src}' -> '${dest}'`,
+      );
+    } finally {
+      Deno.removeSync(tempDir, { recursive: true });
+    }
+  },
+});
+
+Deno.test({
+  name: "[std/node/fs] copy file sync onto itself",
+  fn: () => {
+    const tempDir = Deno.makeTempDirSync();
+    const src = path.join(tempDir, "src.txt");
+    Deno.writeTextFileSync(src, "hello");
+    try {
+      copyFileSync(src, src);
+      assertEquals(Deno.readTextFileSync(src), "hello");
+    } finally {
+      Deno.removeSync(tempDir, { recursive: true });
+    }
+  },
+});
+
 Deno.test("[std/node/fs] copyFile callback isn't called twice if error is thrown", async () => {
   // The correct behaviour is not to catch any errors thrown,
   // but that means there'll be an uncaught error and the test will fail.
@@ -1,6 +1,6 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 import * as path from "@std/path/mod.ts";
-import { assertEquals } from "@std/assert/mod.ts";
+import { assertEquals, assertThrows } from "@std/assert/mod.ts";
 import { assertCallbackErrorUncaught } from "../_test_utils.ts";
 import { realpath, realpathSync } from "node:fs";
 
@@ -53,3 +53,58 @@ Deno.test("[std/node/fs] realpath callback isn't called twice if error is thrown
     },
   });
 });
+
+Deno.test({
+  name: "realpathSync.native reports a symlink loop as ELOOP",
+  ignore: Deno.build.os === "windows",
+  fn() {
+    const tempDir = Deno.makeTempDirSync();
+    const loopA = path.join(tempDir, "loop_a");
+    Deno.symlinkSync(path.join(tempDir, "loop_b"), loopA);
+    Deno.symlinkSync(loopA, path.join(tempDir, "loop_b"));
+    try {
+      const err = assertThrows(() => realpathSync.native(loopA));
+      // deno-lint-ignore no-explicit-any
+      assertEquals((err as any).code, "ELOOP");
+      // deno-lint-ignore no-explicit-any
+      assertEquals((err as any).syscall, "realpath");
+    } finally {
+      Deno.removeSync(tempDir, { recursive: true });
+    }
+  },
+});
+
+Deno.test("realpath.native resolves symlinks", async () => {
+  const tempFile = await Deno.makeTempFile();
+  const tempFileAlias = tempFile + ".alias";
+  await Deno.symlink(tempFile, tempFileAlias);
+  const realPath = await new Promise((resolve, reject) => {
+    realpath.native(tempFileAlias, (err, path) => {
+      if (err) {
+        reject(err);
+        return;
+      }
+      resolve(path);
+    });
+  });
+  assertEquals(realPath, realpathSync(tempFile));
+  assertEquals(realpathSync.native(tempFileAlias), realPath);
+  await Deno.remove(tempFile);
+  await Deno.remove(tempFileAlias);
+});
+
+Deno.test("realpathSync.native reports a missing file as ENOENT", () => {
+  const tempDir = Deno.makeTempDirSync();
+  const missing = path.join(tempDir, "missing");
+  try {
+    const err = assertThrows(() => realpathSync.native(missing));
+    // deno-lint-ignore no-explicit-any
+    assertEquals((err as any).code, "ENOENT");
+    assertEquals(
+      (err as Error).message,
+      `ENOENT: no such file or directory, realpath '${missing}'`,
+    );
+  } finally {
+    Deno.removeSync(tempDir, { recursive: true });
+  }
+});
@@ -1,6 +1,7 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 import { unwatchFile, watch, watchFile } from "node:fs";
 import { assertEquals } from "@std/assert/mod.ts";
+import { basename } from "node:path";
 
 function wait(time: number) {
   return new Promise((resolve) => {
@@ -26,6 +27,29 @@ Deno.test({
   },
 });
 
+Deno.test({
+  name: "watching a file reports a change event when it is modified",
+  async fn() {
+    const file = Deno.makeTempFileSync();
+    const result: Array<[string, string | null]> = [];
+    const watcher = watch(
+      file,
+      (eventType, filename) => result.push([eventType, filename]),
+    );
+    await wait(100);
+    Deno.writeTextFileSync(file, "something");
+    await wait(100);
+    watcher.close();
+    await wait(100);
+    assertEquals(
+      result.some(([eventType, filename]) =>
+        eventType === "change" && filename === basename(file)
+      ),
+      true,
+    );
+  },
+});
+
 Deno.test({
   name: "watching a file with options",
   async fn() {
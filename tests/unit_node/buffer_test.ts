@@ -1,5 +1,5 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
-import { Buffer } from "node:buffer";
+import { Buffer, transcode } from "node:buffer";
 import { assertEquals, assertThrows } from "@std/assert/mod.ts";
 
 Deno.test({
@@ -651,3 +651,18 @@ Deno.test({
     assertEquals([...buf], [0x61, 0x62, 0x63, 0, 0, 0, 0, 0]);
   },
 });
+
+Deno.test({
+  name: "[node/buffer] transcode",
+  fn() {
+    const utf16le = transcode(Buffer.from("€uro"), "utf8", "utf16le");
+    assertEquals(utf16le, Buffer.from("€uro", "utf16le"));
+    assertEquals(transcode(utf16le, "ucs2", "utf8").toString(), "€uro");
+    assertEquals(transcode(Buffer.from("a€"), "utf8", "ascii").toString(), "a?");
+    assertThrows(
+      () => transcode(Buffer.from("abc"), "utf8", "hex"),
+      Error,
+      "Unable to transcode Buffer [U_ILLEGAL_ARGUMENT_ERROR]",
+    );
+  },
+});
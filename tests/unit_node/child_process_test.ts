@@ -2,6 +2,7 @@
 
 import CP from "node:child_process";
 import { Buffer } from "node:buffer";
+import fs from "node:fs";
 import {
   assert,
   assertEquals,
@@ -855,3 +856,23 @@ Deno.test(
     assertEquals(output.stderr, null);
   },
 );
+
+Deno.test(async function spawnStdoutToOpenedFile() {
+  const dir = Deno.makeTempDirSync();
+  const file = path.join(dir, "out.log");
+  const fd = fs.openSync(file, "w");
+  try {
+    const { promise, resolve } = Promise.withResolvers<number | null>();
+    const cp = spawn(
+      Deno.execPath(),
+      ["eval", "console.log('hello')"],
+      { stdio: ["ignore", fd, "inherit"] },
+    );
+    cp.on("close", resolve);
+    assertEquals(await promise, 0);
+    assertEquals(Deno.readTextFileSync(file), "hello\n");
+  } finally {
+    fs.closeSync(fd);
+    Deno.removeSync(dir, { recursive: true });
+  }
+});
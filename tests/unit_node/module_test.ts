@@ -7,7 +7,12 @@ import {
   isBuiltin,
   Module,
 } from "node:module";
-import { assert, assertEquals } from "@std/assert/mod.ts";
+import {
+  assert,
+  assertEquals,
+  assertStringIncludes,
+  assertThrows,
+} from "@std/assert/mod.ts";
 import process from "node:process";
 import * as path from "node:path";
 
@@ -99,3 +104,43 @@ Deno.test("[node/module builtinModules] has 'module' in builtins", () => {
 Deno.test("[node/module findSourceMap] is a function", () => {
   assertEquals(findSourceMap("foo"), undefined);
 });
+
+Deno.test("[node/module require] strips the BOM of JSON files", () => {
+  const require = createRequire(import.meta.url);
+  assertEquals(require("./testdata/json_with_bom.json"), { name: "bom" });
+});
+
+Deno.test("[node/module require] throws ERR_REQUIRE_ESM for .js files in a module package", () => {
+  const require = createRequire(import.meta.url);
+  const err = assertThrows(() => require("./testdata/esm_package/index.js"));
+  // @ts-ignore: code missing from typings
+  assertEquals(err.code, "ERR_REQUIRE_ESM");
+  assertStringIncludes(
+    (err as Error).message,
+    `require() of ES Module ${
+      path.join("testdata", "esm_package", "index.js")
+    }`,
+  );
+});
+
+Deno.test("[node/module require] probes the extensions of a request", () => {
+  const dir = Deno.makeTempDirSync();
+  try {
+    Deno.writeTextFileSync(path.join(dir, "data.json"), '{ "a": 1 }');
+    Deno.writeTextFileSync(path.join(dir, "lib.js"), "module.exports = 'js';");
+    Deno.mkdirSync(path.join(dir, "lib"));
+    Deno.writeTextFileSync(
+      path.join(dir, "lib", "index.js"),
+      "module.exports = 'index';",
+    );
+    const require = createRequire(path.join(dir, "main.js"));
+    assertEquals(require("./data"), { a: 1 });
+    // a file with an extension wins over a directory of the same name
+    assertEquals(require("./lib"), "js");
+    const err = assertThrows(() => require("./missing"));
+    // @ts-ignore: code missing from typings
+    assertEquals(err.code, "MODULE_NOT_FOUND");
+  } finally {
+    Deno.removeSync(dir, { recursive: true });
+  }
+});
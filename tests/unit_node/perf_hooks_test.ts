@@ -1,7 +1,7 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 import * as perfHooks from "node:perf_hooks";
 import { performance, PerformanceObserver } from "node:perf_hooks";
-import { assertEquals, assertThrows } from "@std/assert/mod.ts";
+import { assert, assertEquals, assertThrows } from "@std/assert/mod.ts";
 
 Deno.test({
   name: "[perf_hooks] performance",
@@ -61,3 +61,58 @@ Deno.test({
     });
   },
 });
+
+Deno.test({
+  name: "[perf_hooks] performance.eventLoopUtilization",
+  fn() {
+    const start = performance.eventLoopUtilization();
+    assert(start.utilization >= 0 && start.utilization <= 1);
+
+    const blockUntil = Date.now() + 50;
+    while (Date.now() < blockUntil) {
+      // block the event loop
+    }
+
+    const end = performance.eventLoopUtilization();
+    assert(end.utilization >= 0 && end.utilization <= 1);
+    assert(end.active > start.active);
+
+    const delta = performance.eventLoopUtilization(start);
+    assert(delta.active > 0);
+    assertEquals(
+      performance.eventLoopUtilization(end, start).active,
+      end.active - start.active,
+    );
+  },
+});
+
+Deno.test({
+  name: "[perf_hooks] performance.measure between marks",
+  fn() {
+    performance.mark("perf_hooks_start");
+    const blockUntil = Date.now() + 50;
+    while (Date.now() < blockUntil) {
+      // block the event loop
+    }
+    performance.mark("perf_hooks_end");
+
+    const measure = performance.measure(
+      "perf_hooks_block",
+      "perf_hooks_start",
+      "perf_hooks_end",
+    );
+    // the times may be rounded down to 2ms
+    assert(measure.duration >= 48, `${measure.duration}`);
+    assertEquals(
+      performance.getEntriesByName("perf_hooks_block", "measure").length,
+      1,
+    );
+
+    performance.clearMarks("perf_hooks_start");
+    assertThrows(() =>
+      performance.measure("perf_hooks_cleared", "perf_hooks_start")
+    );
+    performance.clearMarks();
+    assertEquals(performance.getEntriesByType("mark"), []);
+  },
+});
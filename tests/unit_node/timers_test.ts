@@ -1,6 +1,7 @@
 // Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
 
-import { assert, fail } from "@std/assert/mod.ts";
+import { assert, assertEquals, fail } from "@std/assert/mod.ts";
+import * as fs from "node:fs";
 import * as timers from "node:timers";
 import * as timersPromises from "node:timers/promises";
 
@@ -85,3 +86,50 @@ Deno.test("[node/timers setImmediate returns Immediate object]", () => {
   imm.hasRef();
   clearImmediate(imm);
 });
+
+Deno.test("[node/timers unref'd interval lets process exit]", async () => {
+  const { code, stdout } = await new Deno.Command(Deno.execPath(), {
+    args: [
+      "eval",
+      `import { setInterval } from "node:timers";
+      setInterval(() => {}, 1000).unref();
+      console.log("exiting");`,
+    ],
+  }).output();
+  assertEquals(code, 0);
+  assertEquals(new TextDecoder().decode(stdout).trim(), "exiting");
+});
+
+Deno.test("[node/timers ref'd interval keeps process alive]", async () => {
+  const { code, stdout } = await new Deno.Command(Deno.execPath(), {
+    args: [
+      "eval",
+      `import { setInterval, clearInterval } from "node:timers";
+      let ticks = 0;
+      const interval = setInterval(() => {
+        if (++ticks === 3) {
+          clearInterval(interval);
+          console.log("ticks:", ticks);
+        }
+      }, 10);
+      interval.unref();
+      interval.ref();`,
+    ],
+  }).output();
+  assertEquals(code, 0);
+  assertEquals(new TextDecoder().decode(stdout).trim(), "ticks: 3");
+});
+
+Deno.test("[node/timers setImmediate runs first in I/O callback]", async () => {
+  const order: string[] = [];
+  const { promise, resolve } = Promise.withResolvers<void>();
+  fs.readFile(new URL(import.meta.url), () => {
+    timers.setTimeout(() => {
+      order.push("timeout");
+      resolve();
+    }, 0);
+    timers.setImmediate(() => order.push("immediate"));
+  });
+  await promise;
+  assertEquals(order, ["immediate", "timeout"]);
+});
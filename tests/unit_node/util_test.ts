@@ -4,6 +4,7 @@ import {
   assert,
   assertEquals,
   assertStrictEquals,
+  assertStringIncludes,
   assertThrows,
 } from "@std/assert/mod.ts";
 import { stripColor } from "@std/fmt/colors.ts";
@@ -43,6 +44,39 @@ Deno.test({
   },
 });
 
+Deno.test({
+  name: "[util] inspect matches node formatting",
+  fn() {
+    // depth limiting
+    assertEquals(
+      util.inspect({ a: { b: { c: 1 } } }, { depth: 0 }),
+      "{ a: [Object] }",
+    );
+    assertEquals(
+      util.inspect({ a: [1, [2, [3]]] }, { depth: 1 }),
+      "{ a: [ 1, [Array] ] }",
+    );
+
+    // circular markers
+    const circular: Record<string, unknown> = { name: "o" };
+    circular.self = circular;
+    assertEquals(
+      util.inspect(circular),
+      "<ref *1> { name: 'o', self: [Circular *1] }",
+    );
+
+    // null prototype
+    const nullProto = Object.create(null);
+    nullProto.a = 1;
+    assertEquals(util.inspect(nullProto), "[Object: null prototype] { a: 1 }");
+
+    // arrays over the default maxArrayLength of 100
+    const long = util.inspect(new Array(101).fill(0));
+    assertStringIncludes(long, "... 1 more item");
+    assert(!long.includes("... 2 more items"));
+  },
+});
+
 Deno.test({
   name: "[util] isBoolean",
   fn() {
@@ -2,11 +2,14 @@
 import {
   cachedDataVersionTag,
   deserialize,
+  getHeapCodeStatistics,
+  getHeapSpaceStatistics,
   getHeapStatistics,
   serialize,
   setFlagsFromString,
+  writeHeapSnapshot,
 } from "node:v8";
-import { assertEquals } from "@std/assert/mod.ts";
+import { assert, assertEquals } from "@std/assert/mod.ts";
 
 // https://github.com/nodejs/node/blob/a2bbe5ff216bc28f8dac1c36a8750025a93c3827/test/parallel/test-v8-version-tag.js#L6
 Deno.test({
@@ -49,6 +52,65 @@ Deno.test({
   },
 });
 
+Deno.test({
+  name: "getHeapStatistics used_heap_size <= total_heap_size",
+  fn() {
+    const s = getHeapStatistics();
+    assert(s.used_heap_size > 0);
+    assert(s.used_heap_size <= s.total_heap_size);
+  },
+});
+
+Deno.test({
+  name: "getHeapSpaceStatistics success",
+  fn() {
+    const spaces = getHeapSpaceStatistics();
+    const names = spaces.map((space) => space.space_name);
+    assert(names.includes("new_space"));
+    assert(names.includes("old_space"));
+    for (const space of spaces) {
+      assertEquals(Object.keys(space).sort(), [
+        "physical_space_size",
+        "space_available_size",
+        "space_name",
+        "space_size",
+        "space_used_size",
+      ]);
+      assert(space.space_used_size <= space.space_size);
+    }
+  },
+});
+
+Deno.test({
+  name: "getHeapCodeStatistics success",
+  fn() {
+    const s = getHeapCodeStatistics();
+    assertEquals(Object.keys(s).sort(), [
+      "bytecode_and_metadata_size",
+      "code_and_metadata_size",
+      "cpu_profiler_metadata_size",
+      "external_script_source_size",
+    ]);
+    assert(s.code_and_metadata_size > 0);
+  },
+});
+
+Deno.test({
+  name: "writeHeapSnapshot writes to the given file",
+  fn() {
+    const dir = Deno.makeTempDirSync();
+    try {
+      const filename = `${dir}/test.heapsnapshot`;
+      assertEquals(writeHeapSnapshot(filename), filename);
+      const text = Deno.readTextFileSync(filename);
+      assert(text.length > 0);
+      assert(text.startsWith(`{"snapshot"`));
+    } finally {
+      Deno.removeSync(dir, { recursive: true });
+    }
+  },
+});
+
 Deno.test({
   name: "setFlagsFromString",
   fn() {
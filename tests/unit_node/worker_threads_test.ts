@@ -590,3 +590,28 @@ Deno.test({
     channel.port2.close();
   },
 });
+
+Deno.test({
+  name: "[node/worker_threads] Worker.postMessage transfers ArrayBuffer",
+  async fn() {
+    const worker = new workerThreads.Worker(
+      `
+      import { parentPort } from "node:worker_threads";
+      parentPort.once("message", ({ buffer }) => {
+        parentPort.postMessage([buffer.byteLength, [...new Uint8Array(buffer)]]);
+      });
+      `,
+      {
+        eval: true,
+      },
+    );
+    const buffer = new Uint8Array([1, 2, 3, 4]).buffer;
+    worker.postMessage({ buffer }, [buffer]);
+    // detached on the sender
+    assertEquals(buffer.byteLength, 0);
+
+    // intact on the receiver
+    assertEquals((await once(worker, "message"))[0], [4, [1, 2, 3, 4]]);
+    worker.terminate();
+  },
+});